@@ -59,6 +59,13 @@ fn main() {
         .header(format!("{}/include/isl/mat.h", dst.display()))
         .header(format!("{}/include/isl/aff.h", dst.display()))
         .header(format!("{}/include/isl/local_space.h", dst.display()))
+        .header(format!("{}/include/isl/union_set.h", dst.display()))
+        .header(format!("{}/include/isl/union_map.h", dst.display()))
+        .header(format!("{}/include/isl/schedule.h", dst.display()))
+        .header(format!("{}/include/isl/schedule_node.h", dst.display()))
+        .header(format!("{}/include/isl/ilp.h", dst.display()))
+        .header(format!("{}/include/isl/ast.h", dst.display()))
+        .header(format!("{}/include/isl/ast_build.h", dst.display()))
         .clang_arg(format!("-I{}", include_dir.display()))
         .clang_args(additional_include_dir)
         // allow only those functions starts with barvinok and isl and recursively
@@ -1,8 +1,12 @@
 use crate::ident::Ident;
 use crate::local_space::LocalSpace;
+use crate::map::Map;
+use crate::set::Set;
 use crate::space::Space;
 use crate::stat::{isl_bool_to_optional_bool, isl_size_to_optional_u32};
-use crate::value::Value;
+use crate::union_map::UnionMap;
+use crate::union_set::UnionSet;
+use crate::value::{MultiValue, Value};
 use crate::{
     DimType, impl_isl_handle, isl_ctor, isl_flag, isl_project, isl_size, isl_str, isl_transform,
 };
@@ -10,13 +14,19 @@ use std::mem::ManuallyDrop;
 use std::ptr::NonNull;
 
 impl_isl_handle!(Affine, aff);
+impl_isl_handle!(MultiAffine, multi_aff);
+impl_isl_handle!(PiecewiseAffine, pw_aff);
+impl_isl_handle!(PiecewiseMultiAffine, pw_multi_aff);
+impl_isl_handle!(MultiUnionPiecewiseAffine, multi_union_pw_aff);
 
+#[allow(clippy::should_implement_trait)]
 impl<'a> Affine<'a> {
+    isl_ctor!([ctx] from_str, isl_aff_read_from_str, [str] str: &str);
     isl_ctor!(zero_on_domain_space, isl_aff_zero_on_domain_space, space: Space<'a>);
     isl_ctor!(zero_on_domain, isl_aff_zero_on_domain, space: LocalSpace<'a>);
     isl_ctor!(val_on_domain_space, isl_aff_val_on_domain_space, space: Space<'a>, [managed] val: Value<'a>);
     isl_ctor!(val_on_domain, isl_aff_val_on_domain, space: LocalSpace<'a>, [managed] val: Value<'a>);
-    isl_ctor!(var_on_domain, isl_aff_var_on_domain, space: LocalSpace<'a>, [cast(u32)] dim_type: DimType, [trivial] pos: u32);
+    isl_ctor!(var_on_domain, isl_aff_var_on_domain, space: LocalSpace<'a>, dim: Dim);
     isl_ctor!(nan_on_domain_space, isl_aff_nan_on_domain_space, space: Space<'a>);
     isl_ctor!(nan_on_domain, isl_aff_nan_on_domain, space: LocalSpace<'a>);
     isl_ctor!(param_on_domain_space_id, isl_aff_param_on_domain_space_id, space: Space<'a>, [managed] id: Ident<'a>);
@@ -26,23 +36,23 @@ impl<'a> Affine<'a> {
     isl_project!([into(Space)] get_space, isl_aff_get_space);
     isl_project!([into(LocalSpace)] get_domain_local_space, isl_aff_get_domain_local_space);
     isl_project!([into(LocalSpace)] get_local_space, isl_aff_get_local_space);
-    isl_str!(aff_get_dim_name => get_dim_name, [cast(u32)] dim_type: DimType, [trivial] pos: u32);
+    isl_str!(aff_get_dim_name => get_dim_name, dim: Dim);
     isl_project!([into(Value)] get_constant_val, isl_aff_get_constant_val);
-    isl_project!([into(Value)] get_coefficient_val, isl_aff_get_coefficient_val, [cast(u32)] dim_type: DimType, [cast(i32)] pos: u32);
+    isl_project!([into(Value)] get_coefficient_val, isl_aff_get_coefficient_val, dim: Dim);
     isl_project!([into(Value)] get_denominator_val, isl_aff_get_denominator_val);
     isl_transform!(set_constant_si, isl_aff_set_constant_si, [trivial] constant: i32);
     isl_transform!(set_constant_val, isl_aff_set_constant_val, [managed] constant: Value<'a>);
-    isl_transform!(set_coefficient_si, isl_aff_set_coefficient_si, [cast(u32)] dim_type: DimType, [cast(i32)] pos: u32, [trivial] coefficient: i32);
-    isl_transform!(set_coefficient_val, isl_aff_set_coefficient_val, [cast(u32)] dim_type: DimType, [cast(i32)] pos: u32, [managed] coefficient: Value<'a>);
+    isl_transform!(set_coefficient_si, isl_aff_set_coefficient_si, dim: Dim, [trivial] coefficient: i32);
+    isl_transform!(set_coefficient_val, isl_aff_set_coefficient_val, dim: Dim, [managed] coefficient: Value<'a>);
     isl_transform!(add_constant_si, isl_aff_add_constant_si, [trivial] constant: i32);
     isl_transform!(add_constant_val, isl_aff_add_constant_val, [managed] constant: Value<'a>);
     isl_transform!(add_constant_num_si, isl_aff_add_constant_num_si, [trivial] constant: i32);
-    isl_transform!(add_coefficient_si, isl_aff_add_coefficient_si, [cast(u32)] dim_type: DimType, [cast(i32)] pos: u32, [trivial] coefficient: i32);
-    isl_transform!(add_coefficient_val, isl_aff_add_coefficient_val, [cast(u32)] dim_type: DimType, [cast(i32)] pos: u32, [managed] coefficient: Value<'a>);
+    isl_transform!(add_coefficient_si, isl_aff_add_coefficient_si, dim: Dim, [trivial] coefficient: i32);
+    isl_transform!(add_coefficient_val, isl_aff_add_coefficient_val, dim: Dim, [managed] coefficient: Value<'a>);
     isl_flag!(aff_is_cst => is_cst);
     isl_transform!(set_tuple_id, isl_aff_set_tuple_id, [cast(u32)] dim_type: DimType, [managed] id: Ident<'a>);
-    isl_transform!(set_dim_name, isl_aff_set_dim_name, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [str] name: &str);
-    isl_transform!(set_dim_id, isl_aff_set_dim_id, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [managed] id: Ident<'a>);
+    isl_transform!(set_dim_name, isl_aff_set_dim_name, dim: Dim, [str] name: &str);
+    isl_transform!(set_dim_id, isl_aff_set_dim_id, dim: Dim, [managed] id: Ident<'a>);
     isl_flag!(aff_plain_is_equal => plain_is_equal, [ref] other: &Affine<'a>);
     isl_flag!(aff_plain_is_zero => plain_is_zero);
     isl_flag!(aff_is_nan => plain_is_nan);
@@ -62,40 +72,132 @@ impl<'a> Affine<'a> {
     isl_transform!(domain_reverse, isl_aff_domain_reverse);
 }
 
-impl<'a> std::ops::Neg for Affine<'a> {
-    type Output = Affine<'a>;
-    fn neg(self) -> Self::Output {
-        self.checked_neg().unwrap()
-    }
+#[allow(clippy::should_implement_trait)]
+impl<'a> MultiAffine<'a> {
+    isl_ctor!([ctx] from_str, isl_multi_aff_read_from_str, [str] str: &str);
+    isl_project!([into(Space)] get_space, isl_multi_aff_get_space);
+    isl_project!([into(Space)] get_domain_space, isl_multi_aff_get_domain_space);
+    isl_size!(multi_aff_size => size);
+    isl_project!([into(Affine)] get_at, isl_multi_aff_get_at, [cast(i32)] pos: u32);
+    isl_project!([into(MultiValue)] get_constant_multi_val, isl_multi_aff_get_constant_multi_val);
+    isl_transform!([into(Map)] to_map, isl_map_from_multi_aff);
+    isl_transform!([into(Set)] to_set, isl_set_from_multi_aff);
+    isl_transform!([into(PiecewiseMultiAffine)] to_pw_multi_aff, isl_pw_multi_aff_from_multi_aff);
+    isl_transform!(checked_add, isl_multi_aff_add, [managed] other: MultiAffine<'a>);
+    isl_transform!(checked_sub, isl_multi_aff_sub, [managed] other: MultiAffine<'a>);
+    isl_transform!(range_product, isl_multi_aff_range_product, [managed] other: MultiAffine<'a>);
+    isl_flag!(multi_aff_plain_is_equal => plain_is_equal, [ref] other: &MultiAffine<'a>);
 }
 
-impl<'a> std::ops::Add for Affine<'a> {
-    type Output = Affine<'a>;
-    fn add(self, rhs: Self) -> Self::Output {
-        self.checked_add(rhs).unwrap()
+impl<'a> TryFrom<Affine<'a>> for MultiAffine<'a> {
+    fn try_from(aff: Affine<'a>) -> Result<Self, Self::Error> {
+        let ctx = aff.context_ref();
+        let aff = ManuallyDrop::new(aff);
+        let handle = unsafe { barvinok_sys::isl_multi_aff_from_aff(aff.handle.as_ptr()) };
+        let handle = NonNull::new(handle).ok_or_else(|| ctx.last_error_or_unknown())?;
+        Ok(MultiAffine {
+            handle,
+            marker: std::marker::PhantomData,
+        })
     }
+
+    type Error = crate::Error;
+}
+
+#[allow(clippy::should_implement_trait)]
+impl<'a> PiecewiseAffine<'a> {
+    isl_ctor!([ctx] from_str, isl_pw_aff_read_from_str, [str] str: &str);
+    isl_project!([into(Space)] get_space, isl_pw_aff_get_space);
+    isl_project!([into(Space)] get_domain_space, isl_pw_aff_get_domain_space);
+    isl_size!(pw_aff_n_piece => num_pieces);
+    isl_flag!(pw_aff_is_cst => is_cst);
+    isl_flag!(pw_aff_isa_aff => is_aff);
+    isl_transform!([into(Affine)] as_aff, isl_pw_aff_as_aff);
+    isl_transform!([into(Set)] domain, isl_pw_aff_domain);
+    isl_transform!([into(Map)] to_map, isl_map_from_pw_aff);
+    isl_transform!(intersect_domain, isl_pw_aff_intersect_domain, [managed] set: Set<'a>);
+    isl_transform!(gist, isl_pw_aff_gist, [managed] context: Set<'a>);
+    isl_transform!(coalesce, isl_pw_aff_coalesce);
+    isl_transform!(checked_neg, isl_pw_aff_neg);
+    isl_transform!(checked_add, isl_pw_aff_add, [managed] other: PiecewiseAffine<'a>);
+    isl_transform!(checked_sub, isl_pw_aff_sub, [managed] other: PiecewiseAffine<'a>);
+    isl_transform!(checked_mul, isl_pw_aff_mul, [managed] other: PiecewiseAffine<'a>);
+    isl_transform!(checked_div, isl_pw_aff_div, [managed] other: PiecewiseAffine<'a>);
+    isl_transform!(add_constant_val, isl_pw_aff_add_constant_val, [managed] constant: Value<'a>);
+    isl_transform!(scale_val, isl_pw_aff_scale_val, [managed] val: Value<'a>);
+    isl_transform!(scale_down_val, isl_pw_aff_scale_down_val, [managed] scale: Value<'a>);
+    isl_transform!(max, isl_pw_aff_max, [managed] other: PiecewiseAffine<'a>);
+    isl_transform!(min, isl_pw_aff_min, [managed] other: PiecewiseAffine<'a>);
+    isl_transform!([into(Set)] eq_set, isl_pw_aff_eq_set, [managed] other: PiecewiseAffine<'a>);
+    isl_transform!([into(Set)] le_set, isl_pw_aff_le_set, [managed] other: PiecewiseAffine<'a>);
+    isl_transform!([into(Set)] ge_set, isl_pw_aff_ge_set, [managed] other: PiecewiseAffine<'a>);
+    isl_transform!([into(Value)] max_val, isl_pw_aff_max_val);
+    isl_transform!([into(Value)] min_val, isl_pw_aff_min_val);
+    isl_flag!(pw_aff_plain_is_equal => plain_is_equal, [ref] other: &PiecewiseAffine<'a>);
+    isl_flag!(pw_aff_is_equal => checked_eq, [ref] other: &PiecewiseAffine<'a>);
 }
 
-impl<'a> std::ops::Sub for Affine<'a> {
-    type Output = Affine<'a>;
-    fn sub(self, rhs: Self) -> Self::Output {
-        self.checked_sub(rhs).unwrap()
+impl<'a> TryFrom<Affine<'a>> for PiecewiseAffine<'a> {
+    fn try_from(aff: Affine<'a>) -> Result<Self, Self::Error> {
+        let ctx = aff.context_ref();
+        let aff = ManuallyDrop::new(aff);
+        let handle = unsafe { barvinok_sys::isl_pw_aff_from_aff(aff.handle.as_ptr()) };
+        let handle = NonNull::new(handle).ok_or_else(|| ctx.last_error_or_unknown())?;
+        Ok(PiecewiseAffine {
+            handle,
+            marker: std::marker::PhantomData,
+        })
     }
+
+    type Error = crate::Error;
 }
 
-impl<'a> std::ops::Mul for Affine<'a> {
-    type Output = Affine<'a>;
-    fn mul(self, rhs: Self) -> Self::Output {
-        self.checked_mul(rhs).unwrap()
+/// Semantic equality: both sides represent the same function. Errors, such as
+/// mismatched spaces, compare unequal. [`PlainEq`](crate::plain::PlainEq) gives a cheaper
+/// structural equality with a matching hash and order.
+impl PartialEq for PiecewiseAffine<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.checked_eq(other).unwrap_or(false)
     }
 }
-impl<'a> std::ops::Div for Affine<'a> {
-    type Output = Affine<'a>;
-    fn div(self, rhs: Self) -> Self::Output {
-        self.checked_mul(rhs).unwrap()
+
+impl<'a> PiecewiseMultiAffine<'a> {
+    isl_ctor!([ctx] from_str, isl_pw_multi_aff_read_from_str, [str] str: &str);
+    isl_ctor!(from_map, isl_pw_multi_aff_from_map, map: Map<'a>);
+    isl_project!([into(Space)] get_space, isl_pw_multi_aff_get_space);
+    isl_size!(pw_multi_aff_n_piece => num_pieces);
+    isl_flag!(pw_multi_aff_isa_multi_aff => is_multi_aff);
+    isl_project!([into(PiecewiseAffine)] get_at, isl_pw_multi_aff_get_at, [cast(i32)] pos: u32);
+    isl_transform!([into(Set)] domain, isl_pw_multi_aff_domain);
+    isl_transform!([into(Map)] to_map, isl_map_from_pw_multi_aff);
+    isl_transform!([into(Set)] to_set, isl_set_from_pw_multi_aff);
+    isl_transform!(intersect_domain, isl_pw_multi_aff_intersect_domain, [managed] set: Set<'a>);
+    isl_transform!(coalesce, isl_pw_multi_aff_coalesce);
+    isl_flag!(pw_multi_aff_plain_is_equal => plain_is_equal, [ref] other: &PiecewiseMultiAffine<'a>);
+    isl_flag!(pw_multi_aff_is_equal => checked_eq, [ref] other: &PiecewiseMultiAffine<'a>);
+}
+
+impl PartialEq for PiecewiseMultiAffine<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.checked_eq(other).unwrap_or(false)
     }
 }
 
+impl<'a> MultiUnionPiecewiseAffine<'a> {
+    isl_ctor!([ctx] from_str, isl_multi_union_pw_aff_read_from_str, [str] str: &str);
+    isl_ctor!(from_union_map, isl_multi_union_pw_aff_from_union_map, map: UnionMap<'a>);
+    isl_ctor!(multi_val_on_domain, isl_multi_union_pw_aff_multi_val_on_domain, domain: UnionSet<'a>, [managed] values: MultiValue<'a>);
+    isl_project!([into(Space)] get_space, isl_multi_union_pw_aff_get_space);
+    isl_size!(multi_union_pw_aff_size => size);
+    isl_transform!([into(UnionSet)] domain, isl_multi_union_pw_aff_domain);
+    isl_transform!([into(UnionMap)] to_union_map, isl_union_map_from_multi_union_pw_aff);
+    isl_transform!(intersect_domain, isl_multi_union_pw_aff_intersect_domain, [managed] domain: UnionSet<'a>);
+    isl_transform!(union_add, isl_multi_union_pw_aff_union_add, [managed] other: MultiUnionPiecewiseAffine<'a>);
+    isl_transform!(range_product, isl_multi_union_pw_aff_range_product, [managed] other: MultiUnionPiecewiseAffine<'a>);
+    isl_transform!(flat_range_product, isl_multi_union_pw_aff_flat_range_product, [managed] other: MultiUnionPiecewiseAffine<'a>);
+    isl_flag!(multi_union_pw_aff_plain_is_equal => plain_is_equal, [ref] other: &MultiUnionPiecewiseAffine<'a>);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -137,10 +239,58 @@ mod tests {
             let aff1 = Affine::var_on_domain(local_space.clone(), DimType::Param, 0)?;
             let aff2 = Affine::var_on_domain(local_space.clone(), DimType::Out, 1)?;
             let aff3 = Affine::zero_on_domain(local_space)?;
-            let aff4 = aff1 + aff2;
-            let aff5 = aff4 + aff3;
+            let aff4 = (aff1 + aff2)?;
+            let aff5 = (aff4 + aff3)?;
             println!("Affine: {:?}", aff5);
             Ok(())
         })
     }
+
+    #[test]
+    fn test_pw_aff() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let pa = PiecewiseAffine::from_str(
+                ctx,
+                "[n] -> { [i] -> [(i)] : i < n; [i] -> [(n)] : i >= n }",
+            )?;
+            assert_eq!(pa.num_pieces()?, 2);
+            let aff = Affine::from_str(ctx, "[n] -> { [i] -> [(n)] }")?;
+            let pb = PiecewiseAffine::try_from(aff)?;
+            let min = pa.clone().min(pb)?;
+            assert_eq!(min, pa);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_multi_aff() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let ma = MultiAffine::from_str(ctx, "{ [i, j] -> [(i + j), (2j + 1)] }")?;
+            assert_eq!(ma.size()?, 2);
+            let second = ma.get_at(1)?;
+            assert_eq!(second.get_constant_val()?.numerator(), 1);
+            let constants = ma.get_constant_multi_val()?;
+            assert_eq!(constants.get_at(0)?.numerator(), 0);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_multi_union_pw_aff() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let umap = UnionMap::from_str(ctx, "{ S[i, j] -> [i]; T[i] -> [i + 1] }")?;
+            let mupa = MultiUnionPiecewiseAffine::from_union_map(umap.clone())?;
+            assert_eq!(mupa.size()?, 1);
+            let parsed = MultiUnionPiecewiseAffine::from_str(
+                ctx,
+                "[{ S[i, j] -> [(i)]; T[i] -> [(i + 1)] }]",
+            )?;
+            assert!(mupa.plain_is_equal(&parsed)?);
+            assert_eq!(mupa.to_union_map()?, umap);
+            Ok(())
+        })
+    }
 }
@@ -0,0 +1,213 @@
+use std::{mem::ManuallyDrop, ptr::NonNull};
+
+use crate::{
+    aff::{Affine, MultiAffine, PiecewiseAffine, PiecewiseMultiAffine},
+    map::{BasicMap, Map},
+    polynomial::{PiecewiseQuasiPolynomial, QuasiPolynomial},
+    set::{BasicSet, Set},
+    space::Space,
+    union_map::UnionMap,
+    union_set::UnionSet,
+};
+
+/// Objects whose parameters can be reordered and extended to match a model space.
+///
+/// ISL matches parameters by position in many operations. Objects that were built
+/// separately but name their parameters consistently can be brought into a common
+/// parameter space with [`AlignParams::align_params_with`] before they are combined.
+pub trait AlignParams<'a>: Sized {
+    /// The space of the object, used to collect its parameter identifiers.
+    fn param_space(&self) -> Result<Space<'a>, crate::Error>;
+
+    /// Reorder the parameters to match `model`, adding the missing ones.
+    fn align_params_to(self, model: Space<'a>) -> Result<Self, crate::Error>;
+
+    /// Align both objects to the union of their parameters.
+    ///
+    /// The parameters of `self` keep their order, those only present in `other` follow.
+    fn align_params_with<O: AlignParams<'a>>(self, other: O) -> Result<(Self, O), crate::Error> {
+        let model = other.param_space()?.align_params(self.param_space()?)?;
+        let this = self.align_params_to(model.clone())?;
+        let other = other.align_params_to(model)?;
+        Ok((this, other))
+    }
+
+    /// Align both objects to the union of their parameters and combine them with `op`,
+    /// e.g. `set.aligned(map, |set, map| set.apply(map))`.
+    fn aligned<O, R, F>(self, other: O, op: F) -> Result<R, crate::Error>
+    where
+        O: AlignParams<'a>,
+        F: FnOnce(Self, O) -> Result<R, crate::Error>,
+    {
+        let (this, other) = self.align_params_with(other)?;
+        op(this, other)
+    }
+}
+
+macro_rules! impl_align_params {
+    ($type:ident, $get_space:ident, $align:ident) => {
+        impl<'a> AlignParams<'a> for $type<'a> {
+            fn param_space(&self) -> Result<Space<'a>, crate::Error> {
+                let ctx = self.context_ref();
+                let handle = unsafe { barvinok_sys::$get_space(self.handle.as_ptr()) };
+                let handle = NonNull::new(handle).ok_or_else(|| ctx.last_error_or_unknown())?;
+                Ok(Space {
+                    handle,
+                    marker: std::marker::PhantomData,
+                })
+            }
+
+            fn align_params_to(self, model: Space<'a>) -> Result<Self, crate::Error> {
+                let ctx = self.context_ref();
+                let this = ManuallyDrop::new(self);
+                let model = ManuallyDrop::new(model);
+                let handle =
+                    unsafe { barvinok_sys::$align(this.handle.as_ptr(), model.handle.as_ptr()) };
+                let handle = NonNull::new(handle).ok_or_else(|| ctx.last_error_or_unknown())?;
+                Ok($type {
+                    handle,
+                    marker: std::marker::PhantomData,
+                })
+            }
+        }
+    };
+}
+
+impl_align_params!(
+    BasicSet,
+    isl_basic_set_get_space,
+    isl_basic_set_align_params
+);
+impl_align_params!(Set, isl_set_get_space, isl_set_align_params);
+impl_align_params!(
+    BasicMap,
+    isl_basic_map_get_space,
+    isl_basic_map_align_params
+);
+impl_align_params!(Map, isl_map_get_space, isl_map_align_params);
+impl_align_params!(
+    UnionSet,
+    isl_union_set_get_space,
+    isl_union_set_align_params
+);
+impl_align_params!(
+    UnionMap,
+    isl_union_map_get_space,
+    isl_union_map_align_params
+);
+impl_align_params!(Affine, isl_aff_get_space, isl_aff_align_params);
+impl_align_params!(
+    PiecewiseAffine,
+    isl_pw_aff_get_space,
+    isl_pw_aff_align_params
+);
+impl_align_params!(
+    MultiAffine,
+    isl_multi_aff_get_space,
+    isl_multi_aff_align_params
+);
+impl_align_params!(
+    PiecewiseMultiAffine,
+    isl_pw_multi_aff_get_space,
+    isl_pw_multi_aff_align_params
+);
+impl_align_params!(
+    QuasiPolynomial,
+    isl_qpolynomial_get_space,
+    isl_qpolynomial_align_params
+);
+impl_align_params!(Space, isl_space_copy, isl_space_align_params);
+
+/// ISL does not export `isl_pw_qpolynomial_align_params`, so the piecewise
+/// quasi-polynomial takes a detour through a union and is extracted again.
+impl<'a> AlignParams<'a> for PiecewiseQuasiPolynomial<'a> {
+    fn param_space(&self) -> Result<Space<'a>, crate::Error> {
+        self.get_space()
+    }
+
+    fn align_params_to(self, model: Space<'a>) -> Result<Self, crate::Error> {
+        let ctx = self.context_ref();
+        let space = self.get_space()?.align_params(model.clone())?;
+        let this = ManuallyDrop::new(self);
+        let model = ManuallyDrop::new(model);
+        let space = ManuallyDrop::new(space);
+        let handle = unsafe {
+            let union =
+                barvinok_sys::isl_union_pw_qpolynomial_from_pw_qpolynomial(this.handle.as_ptr());
+            let union =
+                barvinok_sys::isl_union_pw_qpolynomial_align_params(union, model.handle.as_ptr());
+            let handle = barvinok_sys::isl_union_pw_qpolynomial_extract_pw_qpolynomial(
+                union,
+                space.handle.as_ptr(),
+            );
+            barvinok_sys::isl_union_pw_qpolynomial_free(union);
+            handle
+        };
+        let handle = NonNull::new(handle).ok_or_else(|| ctx.last_error_or_unknown())?;
+        Ok(PiecewiseQuasiPolynomial {
+            handle,
+            marker: std::marker::PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, DimType};
+
+    #[test]
+    fn test_align_set_with_map() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "[n] -> { [i] : 0 <= i < n }")?;
+            let map = Map::from_str(ctx, "[m, n] -> { [i] -> [j] : j = i + m }")?;
+            let (set, map) = set.align_params_with(map)?;
+            assert_eq!(set.num_params()?, 2);
+            assert_eq!(set.get_dim_name(DimType::Param, 0)?, "n");
+            assert_eq!(map.get_dim_name(DimType::Param, 0)?, "n");
+            assert_eq!(map.get_dim_name(DimType::Param, 1)?, "m");
+
+            let image = Set::from_str(ctx, "[n] -> { [i] : 0 <= i < n }")?.aligned(
+                Map::from_str(ctx, "[m, n] -> { [i] -> [i + m] }")?,
+                |s, m| s.apply(m),
+            )?;
+            let expected = Set::from_str(ctx, "[n, m] -> { [j] : m <= j < n + m }")?;
+            assert_eq!(image, expected);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_align_polynomials() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let domain = Set::from_str(ctx, "[n] -> { [i] }")?.get_space()?;
+            let lhs = QuasiPolynomial::var_on_domain(domain, DimType::Param, 0)?;
+            let lhs = PiecewiseQuasiPolynomial::try_from(lhs)?;
+            let domain = Set::from_str(ctx, "[m, n] -> { [i] }")?.get_space()?;
+            let rhs = QuasiPolynomial::var_on_domain(domain, DimType::Param, 0)?;
+            let rhs = PiecewiseQuasiPolynomial::try_from(rhs)?;
+            let sum = lhs.aligned(rhs, |l, r| l.checked_add(r))?;
+            let space = sum.get_space()?;
+            assert_eq!(space.get_dim(DimType::Param)?, 2);
+            assert_eq!(space.get_dim_name(DimType::Param, 0)?, "n");
+            assert_eq!(space.get_dim_name(DimType::Param, 1)?, "m");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_align_space() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let lhs = Set::from_str(ctx, "[a, b] -> { [i] }")?.get_space()?;
+            let rhs = Set::from_str(ctx, "[c, a] -> { [i] }")?.get_space()?;
+            let model = lhs.align_params(rhs)?;
+            assert_eq!(model.get_dim(DimType::Param)?, 3);
+            assert_eq!(model.get_dim_name(DimType::Param, 0)?, "c");
+            assert_eq!(model.get_dim_name(DimType::Param, 2)?, "b");
+            Ok(())
+        })
+    }
+}
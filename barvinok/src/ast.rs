@@ -0,0 +1,438 @@
+use std::{mem::ManuallyDrop, ptr::NonNull};
+
+use crate::{
+    callback::Callback,
+    ident::Ident,
+    impl_isl_handle, isl_ctor, isl_flag, isl_project, isl_size, isl_transform,
+    list::List,
+    map::Map,
+    printer::{ISL_FORMAT_C, Printer, take_isl_string},
+    schedule::Schedule,
+    set::Set,
+    space::Space,
+    stat::{isl_bool_to_optional_bool, isl_size_to_optional_u32},
+    union_map::UnionMap,
+    value::Value,
+};
+
+impl_isl_handle!([noprint] AstBuild, ast_build);
+impl_isl_handle!(AstExpr, ast_expr);
+impl_isl_handle!(AstNode, ast_node);
+
+pub type AstExprList<'a> = List<'a, AstExpr<'a>>;
+pub type AstNodeList<'a> = List<'a, AstNode<'a>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum AstExprType {
+    Op = barvinok_sys::isl_ast_expr_type_isl_ast_expr_op,
+    Id = barvinok_sys::isl_ast_expr_type_isl_ast_expr_id,
+    Int = barvinok_sys::isl_ast_expr_type_isl_ast_expr_int,
+}
+
+impl AstExprType {
+    fn from_raw(value: barvinok_sys::isl_ast_expr_type) -> Option<Self> {
+        match value {
+            barvinok_sys::isl_ast_expr_type_isl_ast_expr_op => Some(Self::Op),
+            barvinok_sys::isl_ast_expr_type_isl_ast_expr_id => Some(Self::Id),
+            barvinok_sys::isl_ast_expr_type_isl_ast_expr_int => Some(Self::Int),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum AstExprOpType {
+    And = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_and,
+    AndThen = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_and_then,
+    Or = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_or,
+    OrElse = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_or_else,
+    Max = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_max,
+    Min = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_min,
+    Minus = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_minus,
+    Add = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_add,
+    Sub = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_sub,
+    Mul = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_mul,
+    Div = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_div,
+    FdivQ = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_fdiv_q,
+    PdivQ = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_pdiv_q,
+    PdivR = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_pdiv_r,
+    ZdivR = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_zdiv_r,
+    Cond = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_cond,
+    Select = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_select,
+    Eq = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_eq,
+    Le = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_le,
+    Lt = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_lt,
+    Ge = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_ge,
+    Gt = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_gt,
+    Call = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_call,
+    Access = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_access,
+    Member = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_member,
+    AddressOf = barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_address_of,
+}
+
+impl AstExprOpType {
+    fn from_raw(value: barvinok_sys::isl_ast_expr_op_type) -> Option<Self> {
+        match value {
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_and => Some(Self::And),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_and_then => Some(Self::AndThen),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_or => Some(Self::Or),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_or_else => Some(Self::OrElse),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_max => Some(Self::Max),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_min => Some(Self::Min),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_minus => Some(Self::Minus),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_add => Some(Self::Add),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_sub => Some(Self::Sub),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_mul => Some(Self::Mul),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_div => Some(Self::Div),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_fdiv_q => Some(Self::FdivQ),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_pdiv_q => Some(Self::PdivQ),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_pdiv_r => Some(Self::PdivR),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_zdiv_r => Some(Self::ZdivR),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_cond => Some(Self::Cond),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_select => Some(Self::Select),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_eq => Some(Self::Eq),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_le => Some(Self::Le),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_lt => Some(Self::Lt),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_ge => Some(Self::Ge),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_gt => Some(Self::Gt),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_call => Some(Self::Call),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_access => Some(Self::Access),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_member => Some(Self::Member),
+            barvinok_sys::isl_ast_expr_op_type_isl_ast_expr_op_address_of => Some(Self::AddressOf),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum AstNodeType {
+    For = barvinok_sys::isl_ast_node_type_isl_ast_node_for,
+    If = barvinok_sys::isl_ast_node_type_isl_ast_node_if,
+    Block = barvinok_sys::isl_ast_node_type_isl_ast_node_block,
+    Mark = barvinok_sys::isl_ast_node_type_isl_ast_node_mark,
+    User = barvinok_sys::isl_ast_node_type_isl_ast_node_user,
+}
+
+impl AstNodeType {
+    fn from_raw(value: barvinok_sys::isl_ast_node_type) -> Option<Self> {
+        match value {
+            barvinok_sys::isl_ast_node_type_isl_ast_node_for => Some(Self::For),
+            barvinok_sys::isl_ast_node_type_isl_ast_node_if => Some(Self::If),
+            barvinok_sys::isl_ast_node_type_isl_ast_node_block => Some(Self::Block),
+            barvinok_sys::isl_ast_node_type_isl_ast_node_mark => Some(Self::Mark),
+            barvinok_sys::isl_ast_node_type_isl_ast_node_user => Some(Self::User),
+            _ => None,
+        }
+    }
+}
+
+/// An [`AstExpr`] split by its type.
+#[derive(Debug, Clone)]
+pub enum AstExprKind<'a> {
+    Op {
+        op: AstExprOpType,
+        args: Vec<AstExpr<'a>>,
+    },
+    Id(Ident<'a>),
+    Int(Value<'a>),
+}
+
+/// An [`AstNode`] split by its type.
+#[derive(Debug, Clone)]
+pub enum AstNodeKind<'a> {
+    For {
+        iterator: AstExpr<'a>,
+        init: AstExpr<'a>,
+        cond: AstExpr<'a>,
+        inc: AstExpr<'a>,
+        body: AstNode<'a>,
+        degenerate: bool,
+    },
+    If {
+        cond: AstExpr<'a>,
+        then_node: AstNode<'a>,
+        else_node: Option<AstNode<'a>>,
+    },
+    Block(AstNodeList<'a>),
+    Mark {
+        id: Ident<'a>,
+        node: AstNode<'a>,
+    },
+    User(AstExpr<'a>),
+}
+
+impl<'a> AstBuild<'a> {
+    isl_ctor!([ctx] alloc, isl_ast_build_alloc);
+    isl_ctor!(from_context, isl_ast_build_from_context, context: Set<'a>);
+    isl_project!([into(Space)] get_schedule_space, isl_ast_build_get_schedule_space);
+    isl_project!([into(UnionMap)] get_schedule, isl_ast_build_get_schedule);
+    isl_transform!(restrict, isl_ast_build_restrict, [managed] set: Set<'a>);
+    isl_transform!(set_options, isl_ast_build_set_options, [managed] options: UnionMap<'a>);
+    isl_project!([into(AstExpr)] expr_from_set, isl_ast_build_expr_from_set, [managed] set: Set<'a>);
+    isl_project!([into(AstNode)] node_from_schedule, isl_ast_build_node_from_schedule, [managed] schedule: Schedule<'a>);
+    isl_project!([into(AstNode)] node_from_schedule_map, isl_ast_build_node_from_schedule_map, [managed] schedule: UnionMap<'a>);
+
+    /// Generate an AST from a single scheduled map, e.g. `{ S[i, j] -> [i, j] }`.
+    pub fn node_from_map(&self, schedule: Map<'a>) -> Result<AstNode<'a>, crate::Error> {
+        self.node_from_schedule_map(UnionMap::try_from(schedule)?)
+    }
+}
+
+#[allow(clippy::should_implement_trait)]
+impl<'a> AstExpr<'a> {
+    isl_ctor!(from_val, isl_ast_expr_from_val, val: Value<'a>);
+    isl_ctor!(from_id, isl_ast_expr_from_id, id: Ident<'a>);
+    isl_transform!(neg, isl_ast_expr_neg);
+    isl_transform!(add, isl_ast_expr_add, [managed] other: AstExpr<'a>);
+    isl_transform!(sub, isl_ast_expr_sub, [managed] other: AstExpr<'a>);
+    isl_transform!(mul, isl_ast_expr_mul, [managed] other: AstExpr<'a>);
+    isl_transform!(div, isl_ast_expr_div, [managed] other: AstExpr<'a>);
+    isl_transform!(pdiv_q, isl_ast_expr_pdiv_q, [managed] other: AstExpr<'a>);
+    isl_transform!(pdiv_r, isl_ast_expr_pdiv_r, [managed] other: AstExpr<'a>);
+    isl_transform!(and, isl_ast_expr_and, [managed] other: AstExpr<'a>);
+    isl_transform!(and_then, isl_ast_expr_and_then, [managed] other: AstExpr<'a>);
+    isl_transform!(or, isl_ast_expr_or, [managed] other: AstExpr<'a>);
+    isl_transform!(or_else, isl_ast_expr_or_else, [managed] other: AstExpr<'a>);
+    isl_transform!(le, isl_ast_expr_le, [managed] other: AstExpr<'a>);
+    isl_transform!(lt, isl_ast_expr_lt, [managed] other: AstExpr<'a>);
+    isl_transform!(ge, isl_ast_expr_ge, [managed] other: AstExpr<'a>);
+    isl_transform!(gt, isl_ast_expr_gt, [managed] other: AstExpr<'a>);
+    isl_transform!(eq, isl_ast_expr_eq, [managed] other: AstExpr<'a>);
+    isl_transform!(access, isl_ast_expr_access, [managed] indices: AstExprList<'a>);
+    isl_transform!(call, isl_ast_expr_call, [managed] arguments: AstExprList<'a>);
+    isl_transform!(address_of, isl_ast_expr_address_of);
+    isl_project!([into(Value)] int_get_val, isl_ast_expr_int_get_val);
+    isl_project!([into(Ident)] id_get_id, isl_ast_expr_id_get_id);
+    isl_size!(ast_expr_op_get_n_arg => op_get_n_arg);
+    isl_project!([into(AstExpr)] op_get_arg, isl_ast_expr_op_get_arg, [cast(i32)] pos: u32);
+    isl_transform!(set_op_arg, isl_ast_expr_set_op_arg, [cast(i32)] pos: u32, [managed] arg: AstExpr<'a>);
+    isl_flag!(ast_expr_is_equal => checked_eq, [ref] other: &AstExpr<'a>);
+
+    pub fn get_type(&self) -> Result<AstExprType, crate::Error> {
+        let ty = unsafe { barvinok_sys::isl_ast_expr_get_type(self.handle.as_ptr()) };
+        AstExprType::from_raw(ty).ok_or_else(|| self.context_ref().last_error_or_unknown().into())
+    }
+
+    pub fn op_get_type(&self) -> Result<AstExprOpType, crate::Error> {
+        let ty = unsafe { barvinok_sys::isl_ast_expr_op_get_type(self.handle.as_ptr()) };
+        AstExprOpType::from_raw(ty).ok_or_else(|| self.context_ref().last_error_or_unknown().into())
+    }
+
+    pub fn kind(&self) -> Result<AstExprKind<'a>, crate::Error> {
+        match self.get_type()? {
+            AstExprType::Op => {
+                let op = self.op_get_type()?;
+                let args = (0..self.op_get_n_arg()?)
+                    .map(|pos| self.op_get_arg(pos))
+                    .collect::<Result<_, _>>()?;
+                Ok(AstExprKind::Op { op, args })
+            }
+            AstExprType::Id => Ok(AstExprKind::Id(self.id_get_id()?)),
+            AstExprType::Int => Ok(AstExprKind::Int(self.int_get_val()?)),
+        }
+    }
+
+    /// Print the expression in C syntax.
+    pub fn to_c_str(&self) -> Result<String, crate::Error> {
+        let ptr = unsafe { barvinok_sys::isl_ast_expr_to_C_str(self.handle.as_ptr()) };
+        unsafe { take_isl_string(ptr) }
+            .ok_or_else(|| self.context_ref().last_error_or_unknown().into())
+    }
+}
+
+impl PartialEq for AstExpr<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.checked_eq(other).unwrap_or(false)
+    }
+}
+
+impl<'a> AstNode<'a> {
+    isl_ctor!(user_from_expr, isl_ast_node_user_from_expr, expr: AstExpr<'a>);
+    isl_project!([into(AstExpr)] for_get_iterator, isl_ast_node_for_get_iterator);
+    isl_project!([into(AstExpr)] for_get_init, isl_ast_node_for_get_init);
+    isl_project!([into(AstExpr)] for_get_cond, isl_ast_node_for_get_cond);
+    isl_project!([into(AstExpr)] for_get_inc, isl_ast_node_for_get_inc);
+    isl_project!([into(AstNode)] for_get_body, isl_ast_node_for_get_body);
+    isl_flag!(ast_node_for_is_degenerate => for_is_degenerate);
+    isl_project!([into(AstExpr)] if_get_cond, isl_ast_node_if_get_cond);
+    isl_project!([into(AstNode)] if_get_then_node, isl_ast_node_if_get_then_node);
+    isl_flag!(ast_node_if_has_else_node => if_has_else_node);
+    isl_project!([into(AstNode)] if_get_else_node, isl_ast_node_if_get_else_node);
+    isl_project!([into(AstNodeList)] block_get_children, isl_ast_node_block_get_children);
+    isl_project!([into(Ident)] mark_get_id, isl_ast_node_mark_get_id);
+    isl_project!([into(AstNode)] mark_get_node, isl_ast_node_mark_get_node);
+    isl_project!([into(AstExpr)] user_get_expr, isl_ast_node_user_get_expr);
+    isl_project!([into(Ident)] get_annotation, isl_ast_node_get_annotation);
+    isl_transform!(set_annotation, isl_ast_node_set_annotation, [managed] annotation: Ident<'a>);
+
+    pub fn get_type(&self) -> Result<AstNodeType, crate::Error> {
+        let ty = unsafe { barvinok_sys::isl_ast_node_get_type(self.handle.as_ptr()) };
+        AstNodeType::from_raw(ty).ok_or_else(|| self.context_ref().last_error_or_unknown().into())
+    }
+
+    pub fn kind(&self) -> Result<AstNodeKind<'a>, crate::Error> {
+        match self.get_type()? {
+            AstNodeType::For => Ok(AstNodeKind::For {
+                iterator: self.for_get_iterator()?,
+                init: self.for_get_init()?,
+                cond: self.for_get_cond()?,
+                inc: self.for_get_inc()?,
+                body: self.for_get_body()?,
+                degenerate: self.for_is_degenerate()?,
+            }),
+            AstNodeType::If => Ok(AstNodeKind::If {
+                cond: self.if_get_cond()?,
+                then_node: self.if_get_then_node()?,
+                else_node: if self.if_has_else_node()? {
+                    Some(self.if_get_else_node()?)
+                } else {
+                    None
+                },
+            }),
+            AstNodeType::Block => Ok(AstNodeKind::Block(self.block_get_children()?)),
+            AstNodeType::Mark => Ok(AstNodeKind::Mark {
+                id: self.mark_get_id()?,
+                node: self.mark_get_node()?,
+            }),
+            AstNodeType::User => Ok(AstNodeKind::User(self.user_get_expr()?)),
+        }
+    }
+
+    /// Print the AST as C code.
+    pub fn to_c_str(&self) -> Result<String, crate::Error> {
+        let ptr = unsafe { barvinok_sys::isl_ast_node_to_C_str(self.handle.as_ptr()) };
+        unsafe { take_isl_string(ptr) }
+            .ok_or_else(|| self.context_ref().last_error_or_unknown().into())
+    }
+
+    /// Print the AST as C code, letting `print_user` produce the text of every user statement.
+    /// The returned string is written on its own line at the current indentation.
+    pub fn to_c_str_with<F>(&self, print_user: F) -> Result<String, crate::Error>
+    where
+        F: FnMut(&AstNode<'a>) -> Result<String, crate::Error>,
+    {
+        unsafe extern "C" fn callback<'a, F>(
+            printer: *mut barvinok_sys::isl_printer,
+            options: *mut barvinok_sys::isl_ast_print_options,
+            node: *mut barvinok_sys::isl_ast_node,
+            user: *mut std::ffi::c_void,
+        ) -> *mut barvinok_sys::isl_printer
+        where
+            F: FnMut(&AstNode<'a>) -> Result<String, crate::Error>,
+        {
+            unsafe { barvinok_sys::isl_ast_print_options_free(options) };
+            let Some(printer) = (unsafe { Printer::from_raw(printer) }) else {
+                return std::ptr::null_mut();
+            };
+            let Some(node) = NonNull::new(node) else {
+                return std::ptr::null_mut();
+            };
+            let state = unsafe { Callback::<F>::from_user(user) };
+            // the node is only borrowed by the callback
+            let node = ManuallyDrop::new(AstNode {
+                handle: node,
+                marker: std::marker::PhantomData,
+            });
+            let line = state.run(|func| Ok(std::ffi::CString::new(func(&node)?)?));
+            match line {
+                Some(line) => unsafe {
+                    let printer = barvinok_sys::isl_printer_start_line(printer.into_raw());
+                    let printer = barvinok_sys::isl_printer_print_str(printer, line.as_ptr());
+                    barvinok_sys::isl_printer_end_line(printer)
+                },
+                None => std::ptr::null_mut(),
+            }
+        }
+        let ctx = self.context_ref();
+        let printer = unsafe { Printer::new(ctx.0) }
+            .and_then(|p| unsafe {
+                p.transform(barvinok_sys::isl_printer_set_output_format, ISL_FORMAT_C)
+            })
+            .ok_or_else(|| ctx.last_error_or_unknown())?;
+        let mut state = Callback::new(print_user);
+        let options = unsafe { barvinok_sys::isl_ast_print_options_alloc(ctx.0.as_ptr()) };
+        let options = unsafe {
+            barvinok_sys::isl_ast_print_options_set_print_user(
+                options,
+                Some(callback::<F>),
+                state.as_user(),
+            )
+        };
+        let printer = unsafe {
+            Printer::from_raw(barvinok_sys::isl_ast_node_print(
+                self.handle.as_ptr(),
+                printer.into_raw(),
+                options,
+            ))
+        };
+        let string = printer.and_then(|p| p.get_string());
+        state.finish(string.is_none(), ctx)?;
+        string.ok_or_else(|| ctx.last_error_or_unknown().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, union_set::UnionSet};
+
+    #[test]
+    fn test_ast_from_schedule_map() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let build = AstBuild::alloc(ctx)?;
+            let schedule =
+                UnionMap::from_str(ctx, "[n] -> { S[i, j] -> [i, j] : 0 <= j < i < n }")?
+                    .as_map()?;
+            let ast = build.node_from_map(schedule)?;
+            let code = ast.to_c_str()?;
+            println!("{}", code);
+            assert!(code.contains("for (int c0 = 1; c0 < n; c0 += 1)"));
+            assert!(code.contains("S(c0, c1);"));
+
+            let AstNodeKind::For { iterator, body, .. } = ast.kind()? else {
+                panic!("expected an outer for loop");
+            };
+            assert_eq!(iterator.id_get_id()?.name()?, "c0");
+            let AstNodeKind::For { body, .. } = body.kind()? else {
+                panic!("expected an inner for loop");
+            };
+            let AstNodeKind::User(call) = body.kind()? else {
+                panic!("expected a user statement");
+            };
+            let AstExprKind::Op { op, args } = call.kind()? else {
+                panic!("expected a call expression");
+            };
+            assert_eq!(op, AstExprOpType::Call);
+            assert_eq!(args.len(), 3);
+            assert_eq!(args[0].id_get_id()?.name()?, "S");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_ast_from_schedule_with_user_printer() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let domain = UnionSet::from_str(ctx, "{ S[i] : 0 <= i < 8; T[i] : 0 <= i < 8 }")?;
+            let schedule = Schedule::from_domain(domain)?;
+            let build = AstBuild::alloc(ctx)?;
+            let ast = build.node_from_schedule(schedule)?;
+            let code = ast.to_c_str_with(|node| {
+                let AstExprKind::Op { args, .. } = node.user_get_expr()?.kind()? else {
+                    unreachable!()
+                };
+                let name = args[0].id_get_id()?.name()?.to_lowercase();
+                Ok(format!("{}[{}] = 0;", name, args[1].to_c_str()?))
+            })?;
+            println!("{}", code);
+            assert!(code.contains("s[c0] = 0;"));
+            assert!(code.contains("t[c0] = 0;"));
+            assert!(!code.contains("S("));
+            Ok(())
+        })
+    }
+}
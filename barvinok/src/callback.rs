@@ -0,0 +1,169 @@
+use std::{
+    any::Any,
+    ops::ControlFlow,
+    panic::{AssertUnwindSafe, catch_unwind, resume_unwind},
+};
+
+use crate::ContextRef;
+
+/// Value returned by the closure of a `foreach_*` method.
+///
+/// Returning `()` keeps the iteration going. Returning [`ControlFlow::Break`] stops it early;
+/// the `foreach_*` method then returns `Ok(())` as if the iteration had completed.
+pub trait IterationControl {
+    fn into_control_flow(self) -> ControlFlow<()>;
+}
+
+impl IterationControl for () {
+    fn into_control_flow(self) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+impl IterationControl for ControlFlow<()> {
+    fn into_control_flow(self) -> ControlFlow<()> {
+        self
+    }
+}
+
+enum Stop {
+    Break,
+    Error(crate::Error),
+    Panic(Box<dyn Any + Send>),
+}
+
+/// A user closure invoked from an ISL callback.
+///
+/// A pointer to this struct is passed as the `user` argument of the callback. Errors and panics
+/// raised by the closure are stashed here while ISL unwinds its own frames, and are reported
+/// by [`Callback::finish`] once the ISL function has returned.
+pub(crate) struct Callback<F> {
+    func: F,
+    stop: Option<Stop>,
+}
+
+impl<F> Callback<F> {
+    pub(crate) fn new(func: F) -> Self {
+        Self { func, stop: None }
+    }
+
+    pub(crate) fn as_user(&mut self) -> *mut std::ffi::c_void {
+        self as *mut Self as *mut std::ffi::c_void
+    }
+
+    /// # Safety
+    /// `user` must have been obtained from [`Callback::as_user`] on a callback that is still alive.
+    pub(crate) unsafe fn from_user<'b>(user: *mut std::ffi::c_void) -> &'b mut Self {
+        unsafe { &mut *(user as *mut Self) }
+    }
+
+    /// Run `body` on the closure without letting a panic escape.
+    /// Returns `None` if the iteration must stop.
+    pub(crate) fn run<T>(
+        &mut self,
+        body: impl FnOnce(&mut F) -> Result<T, crate::Error>,
+    ) -> Option<T> {
+        if self.stop.is_some() {
+            return None;
+        }
+        match catch_unwind(AssertUnwindSafe(|| body(&mut self.func))) {
+            Ok(Ok(value)) => Some(value),
+            Ok(Err(e)) => {
+                self.stop = Some(Stop::Error(e));
+                None
+            }
+            Err(payload) => {
+                self.stop = Some(Stop::Panic(payload));
+                None
+            }
+        }
+    }
+
+    /// Run the closure of a `foreach_*` method and translate its outcome into an `isl_stat`.
+    pub(crate) fn run_stat<R: IterationControl>(
+        &mut self,
+        body: impl FnOnce(&mut F) -> Result<R, crate::Error>,
+    ) -> barvinok_sys::isl_stat {
+        match self.run(body).map(IterationControl::into_control_flow) {
+            Some(ControlFlow::Continue(())) => barvinok_sys::isl_stat_isl_stat_ok,
+            Some(ControlFlow::Break(())) => {
+                self.stop = Some(Stop::Break);
+                barvinok_sys::isl_stat_isl_stat_error
+            }
+            None => barvinok_sys::isl_stat_isl_stat_error,
+        }
+    }
+
+    /// Run a predicate closure and translate its outcome into an `isl_bool`.
+    pub(crate) fn run_bool(
+        &mut self,
+        body: impl FnOnce(&mut F) -> Result<bool, crate::Error>,
+    ) -> barvinok_sys::isl_bool {
+        match self.run(body) {
+            Some(true) => barvinok_sys::isl_bool_isl_bool_true,
+            Some(false) => barvinok_sys::isl_bool_isl_bool_false,
+            None => barvinok_sys::isl_bool_isl_bool_error,
+        }
+    }
+
+    /// Report how the iteration ended. `failed` tells whether the ISL function signalled an
+    /// error; a panic raised by the closure is resumed here.
+    pub(crate) fn finish(self, failed: bool, ctx: ContextRef<'_>) -> Result<(), crate::Error> {
+        match self.stop {
+            Some(Stop::Panic(payload)) => resume_unwind(payload),
+            Some(Stop::Error(e)) => Err(e),
+            Some(Stop::Break) => Ok(()),
+            None if failed => Err(ctx.last_error_or_unknown().into()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, set::Set};
+
+    #[test]
+    fn test_break_stops_iteration() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "{ [i] : 0 <= i < 100 }")?;
+            let mut visited = 0;
+            set.foreach_point(|_| {
+                visited += 1;
+                Ok(if visited == 3 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                })
+            })?;
+            assert_eq!(visited, 3);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_panic_is_resumed() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "{ [i] : 0 <= i < 100 }")?;
+            let mut visited = 0;
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                set.foreach_point(|_| {
+                    visited += 1;
+                    if visited == 2 {
+                        panic!("stop here");
+                    }
+                    Ok(())
+                })
+            }));
+            let payload = result.unwrap_err();
+            assert_eq!(payload.downcast_ref::<&str>(), Some(&"stop here"));
+            assert_eq!(visited, 2);
+            // the context is still usable after the panic went through ISL
+            assert!(!set.is_empty()?);
+            Ok(())
+        })
+    }
+}
@@ -3,6 +3,7 @@ use std::{mem::ManuallyDrop, ptr::NonNull};
 use crate::{
     DimType,
     aff::Affine,
+    dim::SelectDim,
     impl_isl_handle,
     local_space::LocalSpace,
     nonnull_or_alloc_error,
@@ -242,6 +243,56 @@ impl<'a> Constraint<'a> {
             marker: std::marker::PhantomData,
         })
     }
+    /// Like [`Self::get_dim_name`], but selects the dimension with a [`Dim`](crate::dim::Dim)
+    /// or any other [`SelectDim`].
+    pub fn get_dim_name_by(&self, dim: impl SelectDim) -> Result<&str, crate::Error> {
+        let (dim_type, pos) = dim.resolve_in(self)?;
+        self.get_dim_name(dim_type, pos)
+    }
+    /// Like [`Self::get_coefficient`], but selects the dimension with a [`Dim`](crate::dim::Dim)
+    /// or any other [`SelectDim`].
+    pub fn get_coefficient_by(&self, dim: impl SelectDim) -> Result<Value<'a>, crate::Error> {
+        let (dim_type, pos) = dim.resolve_in(self)?;
+        self.get_coefficient(dim_type, pos)
+            .ok_or_else(|| self.context_ref().last_error_or_unknown().into())
+    }
+    /// Like [`Self::set_coefficient_si`], but selects the dimension with a [`Dim`](crate::dim::Dim)
+    /// or any other [`SelectDim`].
+    pub fn set_coefficient_si_by(self, dim: impl SelectDim, si: i32) -> Result<Self, crate::Error> {
+        let (dim_type, pos) = dim.resolve_in(&self)?;
+        self.set_coefficient_si(dim_type, pos, si)
+    }
+    /// Like [`Self::set_coefficient_val`], but selects the dimension with a
+    /// [`Dim`](crate::dim::Dim) or any other [`SelectDim`].
+    pub fn set_coefficient_val_by(
+        self,
+        dim: impl SelectDim,
+        value: Value<'a>,
+    ) -> Result<Self, crate::Error> {
+        let (dim_type, pos) = dim.resolve_in(&self)?;
+        self.set_coefficient_val(dim_type, pos, value)
+    }
+    /// Like [`Self::is_lower_bound`], but selects the dimension with a [`Dim`](crate::dim::Dim)
+    /// or any other [`SelectDim`].
+    pub fn is_lower_bound_by(&self, dim: impl SelectDim) -> Result<bool, crate::Error> {
+        let (dim_type, pos) = dim.resolve_in(self)?;
+        self.is_lower_bound(dim_type, pos)
+            .ok_or_else(|| self.context_ref().last_error_or_unknown().into())
+    }
+    /// Like [`Self::is_upper_bound`], but selects the dimension with a [`Dim`](crate::dim::Dim)
+    /// or any other [`SelectDim`].
+    pub fn is_upper_bound_by(&self, dim: impl SelectDim) -> Result<bool, crate::Error> {
+        let (dim_type, pos) = dim.resolve_in(self)?;
+        self.is_upper_bound(dim_type, pos)
+            .ok_or_else(|| self.context_ref().last_error_or_unknown().into())
+    }
+    /// Like [`Self::get_bound_type`], but selects the dimension with a [`Dim`](crate::dim::Dim)
+    /// or any other [`SelectDim`].
+    pub fn get_bound_type_by(&self, dim: impl SelectDim) -> Result<Affine<'a>, crate::Error> {
+        let (dim_type, pos) = dim.resolve_in(self)?;
+        self.get_bound_type(dim_type, pos)
+            .ok_or_else(|| self.context_ref().last_error_or_unknown().into())
+    }
     pub fn get_affine(&self) -> Option<Affine<'a>> {
         let handle = unsafe { barvinok_sys::isl_constraint_get_aff(self.handle.as_ptr()) };
         NonNull::new(handle).map(|handle| Affine {
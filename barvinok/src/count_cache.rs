@@ -0,0 +1,330 @@
+//! Memoized counting and summation.
+//!
+//! [`CountCache`] remembers the results of [`Set::cardinality`], [`Map::cardinality`] and
+//! [`PiecewiseQuasiPolynomial::sum`]. Inputs are looked up by a canonical form: they are
+//! coalesced, the counted or summed dimensions lose their tuple and dimension names, and the
+//! disjuncts are sorted with [`PlainCmp`](crate::plain::PlainCmp). Sets that differ only in
+//! the names of their variables therefore share an entry. Parameters, and the domain of a
+//! counted map, appear in the result and are kept as they are.
+//!
+//! A cache opened with [`CountCache::open`] also keeps its entries in a file, so results
+//! survive between runs.
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+use crate::{
+    ContextRef, DimType, Error, map::Map, plain::PlainEq, polynomial::PiecewiseQuasiPolynomial,
+    set::Set, space::Space, traits::IslPrintable,
+};
+
+/// Results of counting and summation, keyed by the canonical form of the input.
+#[derive(Debug, Default)]
+pub struct CountCache {
+    entries: HashMap<String, String>,
+    store: Option<File>,
+    hits: usize,
+    misses: usize,
+}
+
+impl CountCache {
+    /// An empty cache that lives in memory only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the cache stored in the file at `path`, creating it if it does not exist.
+    /// Every new result is appended to the file as soon as it is computed.
+    ///
+    /// The file holds one entry per line, the key and the result separated by a tab.
+    /// A final line without a newline is left over from an interrupted write, it is
+    /// dropped from the file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let store = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        let mut entries = HashMap::new();
+        let mut reader = BufReader::new(&store);
+        let mut line = Vec::new();
+        let mut complete = 0;
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            let Some(text) = line.strip_suffix(b"\n") else {
+                store.set_len(complete)?;
+                break;
+            };
+            let (key, result) = std::str::from_utf8(text)?
+                .split_once('\t')
+                .ok_or(Error::ParseError)?;
+            entries.insert(key.to_string(), result.to_string());
+            complete += line.len() as u64;
+            line.clear();
+        }
+        Ok(Self {
+            entries,
+            store: Some(store),
+            ..Self::default()
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of lookups answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of lookups that had to compute their result.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// [`Set::cardinality`], answered from the cache when possible.
+    pub fn set_cardinality<'a>(
+        &mut self,
+        set: &Set<'a>,
+    ) -> Result<PiecewiseQuasiPolynomial<'a>, Error> {
+        let key = format!("card {}", set_key(set)?);
+        self.lookup(set.context_ref(), key, || set.cardinality_ref())
+    }
+
+    /// [`Map::cardinality`], answered from the cache when possible.
+    pub fn map_cardinality<'a>(
+        &mut self,
+        map: &Map<'a>,
+    ) -> Result<PiecewiseQuasiPolynomial<'a>, Error> {
+        let key = format!("map_card {}", map_key(map)?);
+        self.lookup(map.context_ref(), key, || map.cardinality_ref())
+    }
+
+    /// [`PiecewiseQuasiPolynomial::sum`], answered from the cache when possible.
+    pub fn sum<'a>(
+        &mut self,
+        pwqp: &PiecewiseQuasiPolynomial<'a>,
+    ) -> Result<PiecewiseQuasiPolynomial<'a>, Error> {
+        let key = format!("sum {}", sum_key(pwqp)?);
+        self.lookup(pwqp.context_ref(), key, || pwqp.sum_ref())
+    }
+
+    fn lookup<'a>(
+        &mut self,
+        ctx: ContextRef<'a>,
+        key: String,
+        compute: impl FnOnce() -> Result<PiecewiseQuasiPolynomial<'a>, Error>,
+    ) -> Result<PiecewiseQuasiPolynomial<'a>, Error> {
+        if let Some(result) = self.entries.get(&key) {
+            self.hits += 1;
+            return PiecewiseQuasiPolynomial::from_str(ctx, result);
+        }
+        self.misses += 1;
+        let result = compute()?;
+        let text = result.to_isl_string();
+        if let Some(store) = &mut self.store {
+            writeln!(store, "{key}\t{text}")?;
+        }
+        self.entries.insert(key, text);
+        Ok(result)
+    }
+}
+
+/// Drop the names, tuple id and nesting of the `dim_type` dimensions.
+fn anonymous<'a>(space: Space<'a>, dim_type: DimType) -> Result<Space<'a>, Error> {
+    if space.is_params()? {
+        return Ok(space);
+    }
+    let n = space.get_dim(dim_type)?;
+    space.drop_dims(dim_type, 0, n)?.add_dims(dim_type, n)
+}
+
+/// The space, then the disjuncts in plain order.
+fn disjuncts_key(set: Set<'_>) -> Result<String, Error> {
+    let mut disjuncts = Vec::from(set.get_basic_set_list()?)
+        .into_iter()
+        .map(|basic_set| Set::try_from(basic_set).map(PlainEq))
+        .collect::<Result<Vec<_>, _>>()?;
+    disjuncts.sort();
+    let disjuncts: Vec<_> = disjuncts.iter().map(|set| set.to_isl_string()).collect();
+    Ok(format!(
+        "{} {}",
+        set.get_space()?.to_isl_string(),
+        disjuncts.join("; ")
+    ))
+}
+
+fn set_key(set: &Set<'_>) -> Result<String, Error> {
+    let space = anonymous(set.get_space()?, DimType::Out)?;
+    disjuncts_key(set.clone().coalesce()?.reset_space(space)?)
+}
+
+fn map_key(map: &Map<'_>) -> Result<String, Error> {
+    let wrapped = map.wrap_ref()?;
+    let space = anonymous(wrapped.get_space()?.unwrap()?, DimType::Out)?.wrap()?;
+    disjuncts_key(wrapped.coalesce()?.reset_space(space)?)
+}
+
+fn sum_key(pwqp: &PiecewiseQuasiPolynomial<'_>) -> Result<String, Error> {
+    let space = pwqp.get_domain_space()?;
+    let space = if space.is_wrapping()? {
+        anonymous(space.unwrap()?, DimType::Out)?.wrap()?
+    } else {
+        anonymous(space, DimType::Out)?
+    };
+    let pwqp = pwqp
+        .clone()
+        .pw_qpolynomial_coalesce()?
+        .reset_domain_space(space)?;
+    let mut pieces = Vec::new();
+    pwqp.foreach_piece(|qpoly, domain| {
+        pieces.push((PlainEq(domain), qpoly.to_isl_string()));
+        Ok(())
+    })?;
+    pieces.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let pieces: Vec<_> = pieces
+        .iter()
+        .map(|(domain, qpoly)| format!("{} : {qpoly}", domain.to_isl_string()))
+        .collect();
+    Ok(format!(
+        "{} {}",
+        pwqp.get_space()?.to_isl_string(),
+        pieces.join("; ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+
+    #[test]
+    fn test_canonical_keys() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let key = |text: &str| set_key(&Set::from_str(ctx, text)?);
+            assert_eq!(
+                key("[n] -> { S[i, j] : 0 <= i < j < n }")?,
+                key("[n] -> { T[a, b] : 0 <= a < b < n }")?
+            );
+            assert_eq!(
+                key("{ [i] : 0 <= i < 3 or 10 <= i < 12 }")?,
+                key("{ [k] : 10 <= k < 12 or 0 <= k < 3 }")?
+            );
+            // parameters appear in the result
+            assert_ne!(
+                key("[n] -> { S[i] : 0 <= i < n }")?,
+                key("[m] -> { S[i] : 0 <= i < m }")?
+            );
+
+            let key = |text: &str| map_key(&Map::from_str(ctx, text)?);
+            assert_eq!(
+                key("{ A[i] -> B[j] : 0 <= j < i }")?,
+                key("{ A[i] -> C[k] : 0 <= k < i }")?
+            );
+            // the domain appears in the result
+            assert_ne!(
+                key("{ A[i] -> B[j] : 0 <= j < i }")?,
+                key("{ D[i] -> B[j] : 0 <= j < i }")?
+            );
+
+            let key = |text: &str| sum_key(&PiecewiseQuasiPolynomial::from_str(ctx, text)?);
+            assert_eq!(
+                key("[n] -> { S[i] -> i * n : 0 <= i < n }")?,
+                key("[n] -> { [k] -> k * n : 0 <= k < n }")?
+            );
+            assert_eq!(
+                key("{ [[i] -> [j]] -> i + j : 0 <= j < i }")?,
+                key("{ [[i] -> B[k]] -> i + k : 0 <= k < i }")?
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_lookup_and_store() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("count-cache-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let ctx = Context::new();
+        let result = ctx.scope(|ctx| {
+            let count = |computed: &mut usize| {
+                *computed += 1;
+                PiecewiseQuasiPolynomial::from_str(ctx, "[n] -> { n : n >= 0 }")
+            };
+            let key = || set_key(&Set::from_str(ctx, "[n] -> { S[i] : 0 <= i < n }")?);
+            let mut computed = 0;
+
+            let mut cache = CountCache::open(&path)?;
+            assert!(cache.is_empty());
+            let first = cache.lookup(ctx, key()?, || count(&mut computed))?;
+            let second = cache.lookup(ctx, key()?, || count(&mut computed))?;
+            assert_eq!(computed, 1);
+            assert_eq!((cache.hits(), cache.misses()), (1, 1));
+            assert!(first.plain_is_equal(&second)?);
+            drop(cache);
+
+            let mut cache = CountCache::open(&path)?;
+            assert_eq!(cache.len(), 1);
+            let third = cache.lookup(ctx, key()?, || count(&mut computed))?;
+            assert_eq!(computed, 1);
+            assert!(first.plain_is_equal(&third)?);
+            Ok(())
+        });
+        std::fs::remove_file(&path)?;
+        result
+    }
+
+    #[test]
+    fn test_incomplete_last_line() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("count-cache-{}-cut.txt", std::process::id()));
+        std::fs::write(&path, "card a\t{ 1 }\ncard b\t{ 2")?;
+        let ctx = Context::new();
+        let result = ctx.scope(|ctx| {
+            let mut cache = CountCache::open(&path)?;
+            assert_eq!(cache.len(), 1);
+            let count = || PiecewiseQuasiPolynomial::from_str(ctx, "{ 3 }");
+            cache.lookup(ctx, "card c".to_string(), count)?;
+            drop(cache);
+
+            assert_eq!(
+                std::fs::read_to_string(&path)?,
+                "card a\t{ 1 }\ncard c\t{ 3 }\n"
+            );
+            assert_eq!(CountCache::open(&path)?.len(), 2);
+            Ok(())
+        });
+        std::fs::remove_file(&path)?;
+        result
+    }
+
+    #[test]
+    fn test_count_cache_cardinality() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let mut cache = CountCache::new();
+            let s = Set::from_str(ctx, "[n] -> { S[i, j] : 0 <= i < j < n }")?;
+            let t = Set::from_str(ctx, "[n] -> { T[a, b] : 0 <= a < b < n }")?;
+            let card = cache.set_cardinality(&s)?;
+            assert!((cache.set_cardinality(&t)? - card)?.is_zero()?);
+            assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+            let map = Map::from_str(ctx, "[n] -> { A[i] -> B[j] : 0 <= j < i < n }")?;
+            let card = cache.map_cardinality(&map)?;
+            assert!((cache.map_cardinality(&map)? - card)?.is_zero()?);
+
+            let pwqp = PiecewiseQuasiPolynomial::from_str(ctx, "[n] -> { [i] -> i : 0 <= i < n }")?;
+            let sum = cache.sum(&pwqp)?;
+            assert!((cache.sum(&pwqp)? - sum)?.is_zero()?);
+            assert_eq!((cache.hits(), cache.misses()), (3, 3));
+            Ok(())
+        })
+    }
+}
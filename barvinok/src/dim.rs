@@ -0,0 +1,198 @@
+use std::{marker::PhantomData, ptr::NonNull};
+
+use crate::{DimType, Error, ident::Ident, space::Space, traits::HasSpace};
+
+/// Dimension types searched, in order, by [`Dim::named`] and [`Dim::id`].
+const SEARCHED: [DimType; 3] = [DimType::Param, DimType::In, DimType::Out];
+
+/// Selects a single dimension of an object, either by position or by name.
+///
+/// Methods that take a dimension type and a position have a `_by` variant taking a [`SelectDim`]
+/// instead, such as `Set::fix_si_by`. The selector is resolved against the space of the object
+/// the method is called on.
+/// A dimension that cannot be found is reported as [`Error::VariablePositionOutOfBounds`],
+/// and [`DimType::Cst`] or [`DimType::All`] as [`Error::InvalidDimType`].
+#[derive(Debug, Clone, Copy)]
+pub struct Dim<'s>(Selector<'s>);
+
+#[derive(Debug, Clone, Copy)]
+enum Selector<'s> {
+    At(DimType, u32),
+    Named(Option<DimType>, &'s str),
+    Id(NonNull<barvinok_sys::isl_id>, PhantomData<&'s ()>),
+}
+
+impl<'s> Dim<'s> {
+    pub fn at(dim_type: DimType, pos: u32) -> Self {
+        Dim(Selector::At(dim_type, pos))
+    }
+
+    /// The parameter called `name`.
+    pub fn param(name: &'s str) -> Self {
+        Dim(Selector::Named(Some(DimType::Param), name))
+    }
+
+    pub fn param_at(pos: u32) -> Self {
+        Self::at(DimType::Param, pos)
+    }
+
+    /// The input dimension at `pos`. For affine expressions and quasi-polynomials, these are
+    /// the dimensions of the domain.
+    pub fn input(pos: u32) -> Self {
+        Self::at(DimType::In, pos)
+    }
+
+    /// The output dimension at `pos`, which is also the set dimension of a set.
+    pub fn out(pos: u32) -> Self {
+        Self::at(DimType::Out, pos)
+    }
+
+    pub fn div(pos: u32) -> Self {
+        Self::at(DimType::Div, pos)
+    }
+
+    /// The first dimension called `name` among the parameters, the input and the output
+    /// dimensions.
+    pub fn named(name: &'s str) -> Self {
+        Dim(Selector::Named(None, name))
+    }
+
+    /// The first dimension identified by `id` among the parameters, the input and the
+    /// output dimensions.
+    pub fn id(id: &'s Ident<'_>) -> Self {
+        Dim(Selector::Id(id.handle, PhantomData))
+    }
+}
+
+/// Anything that selects a single dimension: a [`Dim`], a `(DimType, u32)` position,
+/// a dimension name (see [`Dim::named`]) or an [`Ident`] (see [`Dim::id`]).
+pub trait SelectDim: Sized {
+    /// Resolve the selection to a dimension type and a position in `space`.
+    fn resolve(self, space: &Space<'_>) -> Result<(DimType, u32), Error>;
+
+    /// Resolve the selection against the space of `object`.
+    fn resolve_in<'a, T: HasSpace<'a> + ?Sized>(self, object: &T) -> Result<(DimType, u32), Error> {
+        self.resolve(&object.get_space()?)
+    }
+}
+
+impl SelectDim for Dim<'_> {
+    fn resolve(self, space: &Space<'_>) -> Result<(DimType, u32), Error> {
+        match self.0 {
+            Selector::At(DimType::Cst | DimType::All, _) => Err(Error::InvalidDimType),
+            // Spaces carry no divs, so their positions are left for ISL to check.
+            Selector::At(DimType::Div, pos) => Ok((DimType::Div, pos)),
+            Selector::At(dim_type, pos) if pos < space.get_dim(dim_type)? => Ok((dim_type, pos)),
+            Selector::At(..) => Err(Error::VariablePositionOutOfBounds),
+            Selector::Named(Some(dim_type), name) => space
+                .find_dim_by_name(dim_type, name)?
+                .map(|pos| (dim_type, pos))
+                .ok_or(Error::VariablePositionOutOfBounds),
+            Selector::Named(None, name) => {
+                for dim_type in SEARCHED {
+                    if let Some(pos) = space.find_dim_by_name(dim_type, name)? {
+                        return Ok((dim_type, pos));
+                    }
+                }
+                Err(Error::VariablePositionOutOfBounds)
+            }
+            Selector::Id(id, _) => {
+                for dim_type in SEARCHED {
+                    let pos = unsafe {
+                        barvinok_sys::isl_space_find_dim_by_id(
+                            space.handle.as_ptr(),
+                            dim_type as u32,
+                            id.as_ptr(),
+                        )
+                    };
+                    if let Ok(pos) = u32::try_from(pos) {
+                        return Ok((dim_type, pos));
+                    }
+                }
+                Err(Error::VariablePositionOutOfBounds)
+            }
+        }
+    }
+}
+
+impl SelectDim for (DimType, u32) {
+    fn resolve(self, space: &Space<'_>) -> Result<(DimType, u32), Error> {
+        Dim::at(self.0, self.1).resolve(space)
+    }
+}
+
+impl SelectDim for &str {
+    fn resolve(self, space: &Space<'_>) -> Result<(DimType, u32), Error> {
+        Dim::named(self).resolve(space)
+    }
+}
+
+impl SelectDim for &Ident<'_> {
+    fn resolve(self, space: &Space<'_>) -> Result<(DimType, u32), Error> {
+        Dim::id(self).resolve(space)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, set::Set};
+
+    #[test]
+    fn test_resolve() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "[n, m] -> { [i, j] : 0 <= i < n and 0 <= j < m }")?;
+            let space = set.get_space()?;
+            assert!(matches!(
+                Dim::param("m").resolve(&space)?,
+                (DimType::Param, 1)
+            ));
+            assert!(matches!(
+                Dim::named("j").resolve(&space)?,
+                (DimType::Out, 1)
+            ));
+            assert!(matches!(Dim::out(0).resolve(&space)?, (DimType::Out, 0)));
+            assert!(matches!(
+                Dim::param("i").resolve(&space),
+                Err(Error::VariablePositionOutOfBounds)
+            ));
+            assert!(matches!(
+                Dim::out(2).resolve(&space),
+                Err(Error::VariablePositionOutOfBounds)
+            ));
+            assert!(matches!(
+                Dim::at(DimType::Cst, 0).resolve(&space),
+                Err(Error::InvalidDimType)
+            ));
+
+            let id = space.get_dim_id(DimType::Param, 0)?;
+            assert!(matches!(Dim::id(&id).resolve(&space)?, (DimType::Param, 0)));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_methods_accept_dims() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "[n] -> { [i, j] : 0 <= i < n and 0 <= j < 10 }")?;
+            let fixed = set
+                .clone()
+                .fix_si_by(Dim::param("n"), 5)?
+                .fix_si_by("i", 2)?;
+            let expected = Set::from_str(ctx, "[n] -> { [2, j] : n = 5 and 0 <= j < 10 }")?;
+            assert_eq!(fixed, expected);
+            assert_eq!(set.get_dim_name_by(Dim::out(1))?, "j");
+            assert!(matches!(
+                set.clone().fix_si_by("k", 0),
+                Err(Error::VariablePositionOutOfBounds)
+            ));
+            assert!(matches!(
+                set.fix_si_by(Dim::at(DimType::All, 0), 0),
+                Err(Error::InvalidDimType)
+            ));
+            Ok(())
+        })
+    }
+}
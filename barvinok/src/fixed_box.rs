@@ -0,0 +1,80 @@
+use std::ptr::NonNull;
+
+use crate::{
+    aff::MultiAffine, impl_isl_handle, isl_ctor, isl_flag, isl_project, map::Map, set::Set,
+    space::Space, stat::isl_bool_to_optional_bool, value::MultiValue,
+};
+
+impl_isl_handle!(
+    /// A box of constant size whose lower corner is given by an affine offset,
+    /// i.e. `offset(x) <= y < offset(x) + size` in every dimension.
+    FixedBox,
+    fixed_box
+);
+
+impl<'a> FixedBox<'a> {
+    isl_ctor!([ctx] from_str, isl_fixed_box_read_from_str, [str] str: &str);
+    isl_project!([into(Space)] get_space, isl_fixed_box_get_space);
+    isl_flag!(fixed_box_is_valid => is_valid);
+    isl_project!([into(MultiAffine)] get_offset, isl_fixed_box_get_offset);
+    isl_project!([into(MultiValue)] get_size, isl_fixed_box_get_size);
+}
+
+impl<'a> Set<'a> {
+    isl_project!([into(FixedBox)] get_simple_fixed_box_hull, isl_set_get_simple_fixed_box_hull);
+    isl_project!([into(FixedBox)] get_lattice_tile, isl_set_get_lattice_tile);
+}
+
+impl<'a> Map<'a> {
+    isl_project!([into(FixedBox)] get_range_simple_fixed_box_hull, isl_map_get_range_simple_fixed_box_hull);
+    isl_project!([into(FixedBox)] get_range_lattice_tile, isl_map_get_range_lattice_tile);
+
+    /// Box hull of all elements accessed by this access map when its domain is restricted
+    /// to `domain`, e.g. one tile of the iteration space.
+    /// Unlike [`Map::get_range_simple_fixed_box_hull`], the offset only depends on the parameters.
+    pub fn footprint_box(&self, domain: Set<'a>) -> Result<FixedBox<'a>, crate::Error> {
+        self.clone()
+            .intersect_domain(domain)?
+            .range()?
+            .get_simple_fixed_box_hull()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+
+    #[test]
+    fn test_footprint_box() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let access = Map::from_str(ctx, "{ S[i, j] -> A[i + j, 2j] }")?;
+            let tile = Set::from_str(ctx, "[t] -> { S[i, j] : 4t <= i < 4t + 4 and 0 <= j < 8 }")?;
+            let hull = access.footprint_box(tile)?;
+            assert!(hull.is_valid()?);
+            let size = hull.get_size()?;
+            assert_eq!(size.get_at(0)?.numerator(), 11);
+            assert_eq!(size.get_at(1)?.numerator(), 15);
+            let offset = hull.get_offset()?;
+            let expected = MultiAffine::from_str(ctx, "[t] -> { A[(4t), (0)] }")?;
+            assert!(offset.plain_is_equal(&expected)?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_set_fixed_box_hull() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "[n] -> { [i] : n <= i < n + 5 }")?;
+            let hull = set.get_simple_fixed_box_hull()?;
+            assert!(hull.is_valid()?);
+            assert_eq!(hull.get_size()?.get_at(0)?.numerator(), 5);
+
+            let unbounded = Set::from_str(ctx, "[n] -> { [i] : i >= n }")?;
+            assert!(!unbounded.get_simple_fixed_box_hull()?.is_valid()?);
+            Ok(())
+        })
+    }
+}
@@ -84,6 +84,53 @@ impl<'a> Ident<'a> {
         let cstr = unsafe { std::ffi::CStr::from_ptr(cstr) };
         Ok(cstr.to_str()?)
     }
+    /// Copy the identifier into `ctx`, cloning its user data if it is a `T`.
+    /// Otherwise only the name is copied, as by [`Transfer::transfer_to`](crate::transfer::Transfer::transfer_to).
+    pub fn transfer_with_user<'b, T: Any + Clone>(
+        &self,
+        ctx: ContextRef<'b>,
+    ) -> Result<Ident<'b>, crate::Error> {
+        match self.get_user_as::<T>() {
+            Some(user_data) => Ident::new_with_user(ctx, self.name()?, user_data.clone()),
+            None => Ident::new(ctx, self.name()?),
+        }
+    }
+}
+
+/// ISL keeps a single identifier per name and user data in a context, so identifiers are
+/// equal exactly when they are the same object.
+impl PartialEq for Ident<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for Ident<'_> {}
+
+impl std::hash::Hash for Ident<'_> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        unsafe { barvinok_sys::isl_id_get_hash(self.handle.as_ptr()) }.hash(state);
+    }
+}
+
+impl PartialOrd for Ident<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders by name. Identifiers with the same name but different user data are ordered
+/// by address, which is not stable across runs.
+impl Ord for Ident<'_> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let name = |id: &Self| {
+            let name = unsafe { barvinok_sys::isl_id_get_name(id.handle.as_ptr()) };
+            (!name.is_null()).then(|| unsafe { std::ffi::CStr::from_ptr(name) })
+        };
+        name(self)
+            .cmp(&name(other))
+            .then_with(|| self.handle.cmp(&other.handle))
+    }
 }
 
 #[cfg(test)]
@@ -119,6 +166,23 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_ident_eq_hash_ord() {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let x = Ident::new(ctx, "x").unwrap();
+            let y = Ident::new(ctx, "y").unwrap();
+            let x_user = Ident::new_with_user(ctx, "x", 1).unwrap();
+            assert_eq!(x, Ident::new(ctx, "x").unwrap());
+            assert_ne!(x, x_user);
+            assert!(x < y && x_user < y);
+            let ids: std::collections::HashSet<_> =
+                [x.clone(), y, x_user, Ident::new(ctx, "x").unwrap()].into();
+            assert_eq!(ids.len(), 3);
+            assert!(ids.contains(&x));
+        });
+    }
+
     #[test]
     fn test_ident_lifetime() {
         let ctx = Context::new();
@@ -3,17 +3,36 @@ use std::{marker::PhantomData, ptr::NonNull};
 use barvinok_sys::isl_options_set_on_error;
 
 pub mod aff;
+pub mod align;
+pub mod ast;
+pub mod callback;
 pub mod constraint;
+pub mod count_cache;
+pub mod dim;
+pub mod fixed_box;
 pub mod ident;
 pub mod list;
 pub mod local_space;
 pub mod map;
 pub mod mat;
+pub mod ops;
+pub mod optimize;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod plain;
 pub mod point;
 pub mod polynomial;
 mod printer;
+pub mod schedule;
+#[cfg(feature = "serde")]
+pub mod serialize;
 pub mod set;
 pub mod space;
+pub mod stride_info;
+pub mod traits;
+pub mod transfer;
+pub mod union_map;
+pub mod union_set;
 pub mod value;
 pub mod vec;
 
@@ -64,6 +83,10 @@ impl From<barvinok_sys::isl_error> for ISLErrorKind {
 pub enum Error {
     #[error("expected an integer value, got rational or nan")]
     NonIntegralValue,
+    #[error("expected a rational value, got infinity or nan")]
+    NonRationalValue,
+    #[error("value does not fit in the target type")]
+    ValueOutOfRange,
     #[error("invalid string format")]
     ParseError,
     #[error("nul character in string")]
@@ -72,12 +95,28 @@ pub enum Error {
     Utf8Error(#[from] std::str::Utf8Error),
     #[error("variable position out of bounds")]
     VariablePositionOutOfBounds,
+    #[error("dimension type is not valid for this object")]
+    InvalidDimType,
+    #[error("task was cancelled")]
+    Cancelled,
+    #[error("task called back into its own context pool")]
+    ReentrantPool,
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
     #[error("isl error: {0}")]
     IslError(#[from] ISLError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Arguments that ISL takes ownership of.
+///
+/// Such arguments can be passed either by value or by reference. A reference is cloned,
+/// which only increments the reference count of the underlying ISL object.
+pub trait IntoIsl<T> {
+    fn into_isl(self) -> T;
+}
+
 fn nonnull_or_alloc_error<T>(ptr: *mut T) -> NonNull<T> {
     // We don't know the exact layout of T, it is likely to be an opaque ZST.
     // This is the best we can do.
@@ -95,7 +134,26 @@ impl Default for Context {
     }
 }
 
-#[derive(Clone, Copy)]
+/// A context borrowed for the duration of [`Context::scope`].
+///
+/// Every object created in a scope carries its lifetime `'a`, which is invariant and distinct
+/// for each call to `scope`. Objects from different contexts therefore never have the same
+/// type, and the compiler rejects any attempt to combine them, so the wrappers do not check
+/// contexts at run time.
+///
+/// ```compile_fail
+/// # use barvinok::{Context, set::Set};
+/// let (first, second) = (Context::new(), Context::new());
+/// let _ = first.scope(|a| {
+///     second.scope(|b| -> Result<(), barvinok::Error> {
+///         let x = Set::from_str(a, "{ [i] }")?;
+///         let y = Set::from_str(b, "{ [i] }")?;
+///         x.union(y)?;
+///         Ok(())
+///     })
+/// });
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct ContextRef<'a>(
     NonNull<barvinok_sys::isl_ctx>,
@@ -118,13 +176,16 @@ impl<'a> ContextRef<'a> {
         if err == barvinok_sys::isl_error_isl_error_none {
             None
         } else {
-            let message = unsafe {
-                let message = barvinok_sys::isl_ctx_last_error_msg(self.0.as_ptr());
-                std::ffi::CStr::from_ptr(message)
+            // aborted operations record an error without a message
+            let message = unsafe { barvinok_sys::isl_ctx_last_error_msg(self.0.as_ptr()) };
+            let message = if message.is_null() {
+                "no message"
+            } else {
+                unsafe { std::ffi::CStr::from_ptr(message) }
                     .to_str()
                     .unwrap_or("unknown error")
-                    .to_string()
-            };
+            }
+            .to_string();
             Some(ISLError {
                 kind: err.into(),
                 message,
@@ -198,8 +259,9 @@ pub enum DimType {
 }
 
 macro_rules! impl_isl_handle {
-     ([noprint] $RustType:ident, $cname:ident) => {
+     ($(#[$meta:meta])* [noprint] $RustType:ident, $cname:ident) => {
         paste::paste! {
+            $(#[$meta])*
             #[repr(transparent)]
             pub struct $RustType<'a> {
                 pub(crate) handle: std::ptr::NonNull<barvinok_sys::[<isl_ $cname>]>,
@@ -216,6 +278,18 @@ macro_rules! impl_isl_handle {
                 }
             }
 
+            impl<'a> $crate::IntoIsl<$RustType<'a>> for $RustType<'a> {
+                fn into_isl(self) -> $RustType<'a> {
+                    self
+                }
+            }
+
+            impl<'a> $crate::IntoIsl<$RustType<'a>> for &$RustType<'a> {
+                fn into_isl(self) -> $RustType<'a> {
+                    self.clone()
+                }
+            }
+
             impl Drop for $RustType<'_> {
                 fn drop(&mut self) {
                     unsafe { barvinok_sys::[< isl_ $cname _free>](self.handle.as_ptr()) };
@@ -229,10 +303,17 @@ macro_rules! impl_isl_handle {
                     $crate::ContextRef(ptr, std::marker::PhantomData)
                 }
             }
+
+            impl<'a> $crate::traits::IslObject<'a> for $RustType<'a> {
+                fn context_ref(&self) -> $crate::ContextRef<'a> {
+                    $RustType::context_ref(self)
+                }
+            }
         }
     };
-    ($([$flag:ident])? $RustType:ident, $cname:ident) => {
+    ($(#[$meta:meta])* $([$flag:ident])? $RustType:ident, $cname:ident) => {
         paste::paste! {
+            $(#[$meta])*
             #[repr(transparent)]
             pub struct $RustType<'a> {
                 pub(crate) handle: std::ptr::NonNull<barvinok_sys::[<isl_ $cname>]>,
@@ -249,6 +330,18 @@ macro_rules! impl_isl_handle {
                 }
             }
 
+            impl<'a> $crate::IntoIsl<$RustType<'a>> for $RustType<'a> {
+                fn into_isl(self) -> $RustType<'a> {
+                    self
+                }
+            }
+
+            impl<'a> $crate::IntoIsl<$RustType<'a>> for &$RustType<'a> {
+                fn into_isl(self) -> $RustType<'a> {
+                    self.clone()
+                }
+            }
+
             impl Drop for $RustType<'_> {
                 fn drop(&mut self) {
                     unsafe { barvinok_sys::[< isl_ $cname _free>](self.handle.as_ptr()) };
@@ -266,6 +359,22 @@ macro_rules! impl_isl_handle {
                 }
             }
 
+            impl<'a> $crate::traits::IslObject<'a> for $RustType<'a> {
+                fn context_ref(&self) -> $crate::ContextRef<'a> {
+                    $RustType::context_ref(self)
+                }
+            }
+
+            impl<'a> $crate::traits::IslPrintable<'a> for $RustType<'a> {
+                fn dump(&self) {
+                    $RustType::dump(self)
+                }
+
+                fn to_isl_string(&self) -> String {
+                    format!("{:?}", self)
+                }
+            }
+
             $crate::impl_isl_print!($([$flag])* $RustType, $cname);
         }
     };
@@ -290,12 +399,26 @@ macro_rules! isl_macro_impl {
     (@get_access [str] $val:ident) => {
         $val.as_ptr()
     };
+    // ISL takes dimension positions as either `unsigned` or `int`.
+    (@get_access [pos] $val:ident) => {
+        $val as _
+    };
+
+    (@arg_ty [managed] $ty:ty) => {
+        impl $crate::IntoIsl<$ty>
+    };
+    (@arg_ty [$($kind:tt)*] $ty:ty) => {
+        $ty
+    };
 
     (@take [trivial] $val:ident) => {
         $val
     };
+    (@take [pos] $val:ident) => {
+        $val
+    };
     (@take [managed] $val:ident) => {
-        ManuallyDrop::new($val)
+        ManuallyDrop::new($crate::IntoIsl::into_isl($val))
     };
     (@take [ref] $val:ident) => {
         $val
@@ -309,12 +432,29 @@ macro_rules! isl_macro_impl {
 }
 
 macro_rules! isl_ctor {
+    ($func:ident, $sys_fn:ident,
+     $first_name:ident : $first_ty:ty, $dim:ident : Dim
+     $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)? ) => {
+        $crate::isl_ctor!($func, $sys_fn, $first_name: $first_ty, [cast(u32)] dim_type: $crate::DimType, [pos] pos: u32 $(, [$kind $(($param))?] $name: $ty)*);
+
+        paste::paste! {
+            #[doc = concat!("Like [`Self::", stringify!($func), "`], but selects the dimension with a [`Dim`](crate::dim::Dim) or any other [`SelectDim`](crate::dim::SelectDim).")]
+            pub fn [<$func _by>](
+                $first_name: $first_ty,
+                $dim: impl $crate::dim::SelectDim
+                $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
+            ) -> Result<Self, crate::Error> {
+                let (dim_type, pos) = $crate::dim::SelectDim::resolve_in($dim, &$first_name)?;
+                Self::$func($first_name, dim_type, pos $(, $name)*)
+            }
+        }
+    };
     ($func:ident, $sys_fn:ident,
      $first_name:ident : $first_ty:ty
      $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)? ) => {
         pub fn $func(
             $first_name: $first_ty
-            $(, $name: $ty )*
+            $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
         ) -> Result<Self, crate::Error> {
             // pull the ContextRef from the first argument
             let ctx = $first_name.context_ref();
@@ -345,7 +485,7 @@ macro_rules! isl_ctor {
         $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)? ) => {
            pub fn $func(
                ctx: $crate::ContextRef<'a>
-               $(, $name: $ty )*
+               $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
            ) -> Result<Self, crate::Error> {
                $(
                    let $name = $crate::isl_macro_impl!(@take [$kind $(($param))*] $name);
@@ -371,11 +511,43 @@ macro_rules! isl_ctor {
 }
 
 macro_rules! isl_transform {
+    ($func:ident, $sys_fn:ident, $dim:ident : Dim
+     $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)? ) => {
+        $crate::isl_transform!($func, $sys_fn, [cast(u32)] dim_type: $crate::DimType, [pos] pos: u32 $(, [$kind $(($param))?] $name: $ty)*);
+
+        paste::paste! {
+            #[doc = concat!("Like [`Self::", stringify!($func), "`], but selects the dimension with a [`Dim`](crate::dim::Dim) or any other [`SelectDim`](crate::dim::SelectDim).")]
+            pub fn [<$func _by>](
+                self: Self,
+                $dim: impl $crate::dim::SelectDim
+                $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
+            ) -> Result<Self, crate::Error> {
+                let (dim_type, pos) = $crate::dim::SelectDim::resolve_in($dim, &self)?;
+                self.$func(dim_type, pos $(, $name)*)
+            }
+        }
+    };
+    ([into ($target:ident)] $func:ident, $sys_fn:ident, $dim:ident : Dim
+     $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)? ) => {
+        $crate::isl_transform!([into ($target)] $func, $sys_fn, [cast(u32)] dim_type: $crate::DimType, [pos] pos: u32 $(, [$kind $(($param))?] $name: $ty)*);
+
+        paste::paste! {
+            #[doc = concat!("Like [`Self::", stringify!($func), "`], but selects the dimension with a [`Dim`](crate::dim::Dim) or any other [`SelectDim`](crate::dim::SelectDim).")]
+            pub fn [<$func _by>](
+                self: Self,
+                $dim: impl $crate::dim::SelectDim
+                $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
+            ) -> Result<$target<'a>, crate::Error> {
+                let (dim_type, pos) = $crate::dim::SelectDim::resolve_in($dim, &self)?;
+                self.$func(dim_type, pos $(, $name)*)
+            }
+        }
+    };
     ($func:ident, $sys_fn:ident
      $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)? ) => {
         pub fn $func(
             self: Self
-            $(, $name: $ty )*
+            $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
         ) -> Result<Self, crate::Error> {
             // pull the ContextRef from the first argument
             let ctx = self.context_ref();
@@ -401,12 +573,22 @@ macro_rules! isl_transform {
                     marker: std::marker::PhantomData,
                 })
         }
+
+        paste::paste! {
+            #[doc = concat!("Like [`Self::", stringify!($func), "`], but borrows `self`.")]
+            pub fn [<$func _ref>](
+                &self
+                $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
+            ) -> Result<Self, crate::Error> {
+                self.clone().$func($($name),*)
+            }
+        }
     };
     ([into ($target:ident)]  $func:ident, $sys_fn:ident
      $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)? ) => {
         pub fn $func(
             self: Self
-            $(, $name: $ty )*
+            $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
         ) -> Result<$target<'a>, crate::Error> {
             // pull the ContextRef from the first argument
             let ctx = self.context_ref();
@@ -432,15 +614,41 @@ macro_rules! isl_transform {
                     marker: std::marker::PhantomData,
                 })
         }
+
+        paste::paste! {
+            #[doc = concat!("Like [`Self::", stringify!($func), "`], but borrows `self`.")]
+            pub fn [<$func _ref>](
+                &self
+                $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
+            ) -> Result<$target<'a>, crate::Error> {
+                self.clone().$func($($name),*)
+            }
+        }
     };
 }
 
 macro_rules! isl_project {
+    ([into ($target:ident)] $func:ident, $sys_fn:ident, $dim:ident : Dim
+     $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)? ) => {
+        $crate::isl_project!([into ($target)] $func, $sys_fn, [cast(u32)] dim_type: $crate::DimType, [pos] pos: u32 $(, [$kind $(($param))?] $name: $ty)*);
+
+        paste::paste! {
+            #[doc = concat!("Like [`Self::", stringify!($func), "`], but selects the dimension with a [`Dim`](crate::dim::Dim) or any other [`SelectDim`](crate::dim::SelectDim).")]
+            pub fn [<$func _by>](
+                &self,
+                $dim: impl $crate::dim::SelectDim
+                $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
+            ) -> Result<$target<'a>, crate::Error> {
+                let (dim_type, pos) = $crate::dim::SelectDim::resolve_in($dim, self)?;
+                self.$func(dim_type, pos $(, $name)*)
+            }
+        }
+    };
     ([into ($target:ident)]  $func:ident, $sys_fn:ident
      $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)? ) => {
         pub fn $func(
             &self
-            $(, $name: $ty )*
+            $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
         ) -> Result<$target<'a>, crate::Error> {
             $(
                 let $name = $crate::isl_macro_impl!(@take [$kind $(($param))*] $name);
@@ -463,10 +671,51 @@ macro_rules! isl_project {
     };
 }
 
+/// For ISL functions that also report whether the result is exact or an approximation.
+macro_rules! isl_transform_exact {
+    ($func:ident, $sys_fn:ident) => {
+        pub fn $func(self: Self) -> Result<(Self, bool), crate::Error> {
+            // pull the ContextRef from the first argument
+            let ctx = self.context_ref();
+            let this = std::mem::ManuallyDrop::new(self);
+            let mut exact = barvinok_sys::isl_bool_isl_bool_error;
+
+            // call the raw C function
+            let raw = unsafe { barvinok_sys::$sys_fn(this.handle.as_ptr(), &mut exact) };
+
+            // wrap in NonNull, use saved `ctx` on error
+            let result = NonNull::new(raw)
+                .ok_or_else(|| ctx.last_error_or_unknown())
+                .map(|handle| Self {
+                    handle,
+                    marker: std::marker::PhantomData,
+                })?;
+            let exact = $crate::stat::isl_bool_to_optional_bool(exact)
+                .ok_or_else(|| ctx.last_error_or_unknown())?;
+            Ok((result, exact))
+        }
+    };
+}
+
 macro_rules! isl_flag {
+    ($isl_func:ident => $fn_name:ident, $dim:ident : Dim $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)?) => {
+        $crate::isl_flag!($isl_func => $fn_name, [cast(u32)] dim_type: $crate::DimType, [pos] pos: u32 $(, [$kind $(($param))?] $name: $ty)*);
+
+        paste::paste! {
+            #[doc = concat!("Like [`Self::", stringify!($fn_name), "`], but selects the dimension with a [`Dim`](crate::dim::Dim) or any other [`SelectDim`](crate::dim::SelectDim).")]
+            pub fn [<$fn_name _by>](
+                &self,
+                $dim: impl $crate::dim::SelectDim
+                $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
+            ) -> Result<bool, $crate::Error> {
+                let (dim_type, pos) = $crate::dim::SelectDim::resolve_in($dim, self)?;
+                self.$fn_name(dim_type, pos $(, $name)*)
+            }
+        }
+    };
     ($isl_func:ident => $fn_name:ident $(, [$kind:ident $(($param:ty))?]$name:ident : $ty:ty )* $(,)?) => {
         paste::paste! {
-            pub fn $fn_name(&self $(, $name: $ty )*) -> Result<bool, $crate::Error> {
+            pub fn $fn_name(&self $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*) -> Result<bool, $crate::Error> {
                 $(
                     let $name = $crate::isl_macro_impl!(@take [$kind $(($param))*] $name);
                 )*
@@ -480,9 +729,24 @@ macro_rules! isl_flag {
 }
 
 macro_rules! isl_str {
+    ($isl_func:ident => $fn_name:ident, $dim:ident : Dim $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)?) => {
+        $crate::isl_str!($isl_func => $fn_name, [cast(u32)] dim_type: $crate::DimType, [pos] pos: u32 $(, [$kind $(($param))?] $name: $ty)*);
+
+        paste::paste! {
+            #[doc = concat!("Like [`Self::", stringify!($fn_name), "`], but selects the dimension with a [`Dim`](crate::dim::Dim) or any other [`SelectDim`](crate::dim::SelectDim).")]
+            pub fn [<$fn_name _by>](
+                &self,
+                $dim: impl $crate::dim::SelectDim
+                $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*
+            ) -> Result<&str, $crate::Error> {
+                let (dim_type, pos) = $crate::dim::SelectDim::resolve_in($dim, self)?;
+                self.$fn_name(dim_type, pos $(, $name)*)
+            }
+        }
+    };
     ($isl_func:ident => $fn_name:ident $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)?) => {
         paste::paste! {
-            pub fn $fn_name(&self $(, $name: $ty )*) -> Result<&str, $crate::Error> {
+            pub fn $fn_name(&self $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*) -> Result<&str, $crate::Error> {
                 $(
                     let $name = $crate::isl_macro_impl!(@take [$kind $(($param))*] $name);
                 )*
@@ -501,7 +765,7 @@ macro_rules! isl_str {
 macro_rules! isl_size {
     ($isl_func:ident => $fn_name:ident $(, [$kind:ident $(($param:ty))?] $name:ident : $ty:ty )* $(,)?) => {
         paste::paste! {
-            pub fn $fn_name(&self $(, $name: $ty )*) -> Result<u32, $crate::Error> {
+            pub fn $fn_name(&self $(, $name: $crate::isl_macro_impl!(@arg_ty [$kind $(($param))*] $ty) )*) -> Result<u32, $crate::Error> {
                 $(
                     let $name = $crate::isl_macro_impl!(@take [$kind $(($param))*] $name);
                 )*
@@ -521,6 +785,7 @@ pub(crate) use isl_project;
 pub(crate) use isl_size;
 pub(crate) use isl_str;
 pub(crate) use isl_transform;
+pub(crate) use isl_transform_exact;
 
 #[cfg(test)]
 mod tests {
@@ -618,6 +883,24 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_borrowed_arguments() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let domain = Set::from_str(ctx, "[n] -> { [i] : 0 <= i < n }")?;
+            let map = Map::from_str(ctx, "[n] -> { [i] -> [i + 1] }")?;
+            // owned and borrowed arguments give the same result
+            let borrowed = map.intersect_domain_ref(&domain)?;
+            let owned = map.clone().intersect_domain(domain.clone())?;
+            assert_eq!(borrowed, owned);
+            // the borrowed receiver and argument are still usable
+            let image = domain.apply_ref(&map)?;
+            assert_eq!(image, Set::from_str(ctx, "[n] -> { [i] : 1 <= i <= n }")?);
+            assert!(domain.subset(&domain.union_ref(&image)?)?);
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_from_args() {
         unsafe { Context::from_args(["--verbose"].into_iter()).unwrap() };
@@ -1,6 +1,6 @@
 use std::{ffi::c_char, mem::ManuallyDrop, ptr::NonNull};
 
-use crate::{ContextRef, nonnull_or_alloc_error, printer::ISLPrint};
+use crate::{ContextRef, IntoIsl, callback::Callback, nonnull_or_alloc_error, printer::ISLPrint};
 
 #[allow(clippy::missing_safety_doc)]
 pub trait ListRawAPI {
@@ -322,6 +322,182 @@ impl_list_raw_api!(
     }
 );
 
+impl_list_raw_api!(
+    crate::map::Map<'_>,
+    handle = barvinok_sys::isl_map,
+    list_handle = barvinok_sys::isl_map_list,
+    prefix = map,
+    unsafe fn get_handle(&self) -> *mut Self::Handle {
+        self.handle.as_ptr()
+    },
+    unsafe fn from_raw_handle(handle: NonNull<Self::Handle>) -> Self {
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+);
+
+impl_list_raw_api!(
+    crate::map::BasicMap<'_>,
+    handle = barvinok_sys::isl_basic_map,
+    list_handle = barvinok_sys::isl_basic_map_list,
+    prefix = basic_map,
+    unsafe fn get_handle(&self) -> *mut Self::Handle {
+        self.handle.as_ptr()
+    },
+    unsafe fn from_raw_handle(handle: NonNull<Self::Handle>) -> Self {
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+);
+
+impl_list_raw_api!(
+    crate::union_set::UnionSet<'_>,
+    handle = barvinok_sys::isl_union_set,
+    list_handle = barvinok_sys::isl_union_set_list,
+    prefix = union_set,
+    unsafe fn get_handle(&self) -> *mut Self::Handle {
+        self.handle.as_ptr()
+    },
+    unsafe fn from_raw_handle(handle: NonNull<Self::Handle>) -> Self {
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+);
+
+impl_list_raw_api!(
+    crate::ast::AstExpr<'_>,
+    handle = barvinok_sys::isl_ast_expr,
+    list_handle = barvinok_sys::isl_ast_expr_list,
+    prefix = ast_expr,
+    unsafe fn get_handle(&self) -> *mut Self::Handle {
+        self.handle.as_ptr()
+    },
+    unsafe fn from_raw_handle(handle: NonNull<Self::Handle>) -> Self {
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+);
+
+impl_list_raw_api!(
+    crate::ast::AstNode<'_>,
+    handle = barvinok_sys::isl_ast_node,
+    list_handle = barvinok_sys::isl_ast_node_list,
+    prefix = ast_node,
+    unsafe fn get_handle(&self) -> *mut Self::Handle {
+        self.handle.as_ptr()
+    },
+    unsafe fn from_raw_handle(handle: NonNull<Self::Handle>) -> Self {
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+);
+
+impl_list_raw_api!(
+    crate::union_map::UnionMap<'_>,
+    handle = barvinok_sys::isl_union_map,
+    list_handle = barvinok_sys::isl_union_map_list,
+    prefix = union_map,
+    unsafe fn get_handle(&self) -> *mut Self::Handle {
+        self.handle.as_ptr()
+    },
+    unsafe fn from_raw_handle(handle: NonNull<Self::Handle>) -> Self {
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+);
+
+impl_list_raw_api!(
+    crate::ident::Ident<'_>,
+    handle = barvinok_sys::isl_id,
+    list_handle = barvinok_sys::isl_id_list,
+    prefix = id,
+    unsafe fn get_handle(&self) -> *mut Self::Handle {
+        self.handle.as_ptr()
+    },
+    unsafe fn from_raw_handle(handle: NonNull<Self::Handle>) -> Self {
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+);
+
+impl_list_raw_api!(
+    crate::aff::PiecewiseAffine<'_>,
+    handle = barvinok_sys::isl_pw_aff,
+    list_handle = barvinok_sys::isl_pw_aff_list,
+    prefix = pw_aff,
+    unsafe fn get_handle(&self) -> *mut Self::Handle {
+        self.handle.as_ptr()
+    },
+    unsafe fn from_raw_handle(handle: NonNull<Self::Handle>) -> Self {
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+);
+
+impl_list_raw_api!(
+    crate::aff::PiecewiseMultiAffine<'_>,
+    handle = barvinok_sys::isl_pw_multi_aff,
+    list_handle = barvinok_sys::isl_pw_multi_aff_list,
+    prefix = pw_multi_aff,
+    unsafe fn get_handle(&self) -> *mut Self::Handle {
+        self.handle.as_ptr()
+    },
+    unsafe fn from_raw_handle(handle: NonNull<Self::Handle>) -> Self {
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+);
+
+impl_list_raw_api!(
+    crate::polynomial::QuasiPolynomial<'_>,
+    handle = barvinok_sys::isl_qpolynomial,
+    list_handle = barvinok_sys::isl_qpolynomial_list,
+    prefix = qpolynomial,
+    unsafe fn get_handle(&self) -> *mut Self::Handle {
+        self.handle.as_ptr()
+    },
+    unsafe fn from_raw_handle(handle: NonNull<Self::Handle>) -> Self {
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+);
+
+impl_list_raw_api!(
+    crate::polynomial::PiecewiseQuasiPolynomial<'_>,
+    handle = barvinok_sys::isl_pw_qpolynomial,
+    list_handle = barvinok_sys::isl_pw_qpolynomial_list,
+    prefix = pw_qpolynomial,
+    unsafe fn get_handle(&self) -> *mut Self::Handle {
+        self.handle.as_ptr()
+    },
+    unsafe fn from_raw_handle(handle: NonNull<Self::Handle>) -> Self {
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+);
+
 pub struct List<'a, T: ListRawAPI> {
     pub(crate) handle: NonNull<T::ListHandle>,
     pub(crate) marker: std::marker::PhantomData<*mut &'a [&'a T]>,
@@ -389,6 +565,159 @@ impl<'a, T: ListRawAPI + 'a> List<'a, T> {
     pub fn iter<'u>(&'u self) -> Iter<'u, 'a, T> {
         Iter::new(self)
     }
+
+    /// Build a list in `ctx`. A list always needs a context, so there is no `FromIterator`.
+    pub fn from_iter_in<I: IntoIterator<Item = T>>(ctx: ContextRef<'a>, iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut list = Self::new(ctx, iter.size_hint().0);
+        list.extend(iter);
+        list
+    }
+
+    pub fn from_vec(ctx: ContextRef<'a>, vec: Vec<T>) -> Self {
+        Self::from_iter_in(ctx, vec)
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().collect()
+    }
+
+    /// Insert `el` at `index`, shifting the following elements.
+    ///
+    /// # Panics
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, el: T) {
+        assert!(index <= self.len(), "insertion index out of bounds");
+        let el = ManuallyDrop::new(el);
+        let handle =
+            unsafe { T::list_insert(self.handle.as_ptr(), index as u32, T::get_handle(&el)) };
+        self.handle = nonnull_or_alloc_error(handle);
+    }
+
+    /// Remove the elements in `range`.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds.
+    pub fn drop_range(&mut self, range: std::ops::Range<usize>) {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "drop range out of bounds"
+        );
+        let handle = unsafe {
+            T::list_drop(
+                self.handle.as_ptr(),
+                range.start as u32,
+                (range.end - range.start) as u32,
+            )
+        };
+        self.handle = nonnull_or_alloc_error(handle);
+    }
+
+    pub fn clear(&mut self) {
+        let handle = unsafe { T::list_clear(self.handle.as_ptr()) };
+        self.handle = nonnull_or_alloc_error(handle);
+    }
+
+    /// # Panics
+    /// Panics if either index is out of bounds.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        assert!(a < self.len() && b < self.len(), "swap index out of bounds");
+        let handle = unsafe { T::list_swap(self.handle.as_ptr(), a as u32, b as u32) };
+        self.handle = nonnull_or_alloc_error(handle);
+    }
+
+    pub fn reverse(&mut self) {
+        let handle = unsafe { T::list_reverse(self.handle.as_ptr()) };
+        self.handle = nonnull_or_alloc_error(handle);
+    }
+
+    pub fn concat(self, other: Self) -> Self {
+        let this = ManuallyDrop::new(self);
+        let other = ManuallyDrop::new(other);
+        let handle = unsafe { T::list_concat(this.handle.as_ptr(), other.handle.as_ptr()) };
+        Self {
+            handle: nonnull_or_alloc_error(handle),
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Sort the list with `isl_*_list_sort`. A panic in `cmp` is resumed once ISL has returned.
+    pub fn sort_by<F>(&mut self, cmp: F) -> Result<(), crate::Error>
+    where
+        F: FnMut(&T, &T) -> std::cmp::Ordering,
+    {
+        unsafe extern "C" fn callback<T: ListRawAPI, F>(
+            a: *mut T::Handle,
+            b: *mut T::Handle,
+            user: *mut std::ffi::c_void,
+        ) -> i32
+        where
+            F: FnMut(&T, &T) -> std::cmp::Ordering,
+        {
+            // a comparison cannot report an error, so null elements compare equal
+            let (Some(a), Some(b)) = (NonNull::new(a), NonNull::new(b)) else {
+                return 0;
+            };
+            let state = unsafe { Callback::<F>::from_user(user) };
+            // the elements are only borrowed by the callback
+            let a = ManuallyDrop::new(unsafe { T::from_raw_handle(a) });
+            let b = ManuallyDrop::new(unsafe { T::from_raw_handle(b) });
+            match state.run(|cmp| Ok(cmp(&a, &b))) {
+                Some(ordering) => ordering as i32,
+                None => 0,
+            }
+        }
+        let ctx = self.context();
+        let mut state = Callback::new(cmp);
+        let handle =
+            unsafe { T::list_sort(self.handle.as_ptr(), callback::<T, F>, state.as_user()) };
+        // ISL frees the list when sorting fails, so the handle is replaced in any case
+        match NonNull::new(handle) {
+            Some(handle) => self.handle = handle,
+            None => {
+                let empty = unsafe { T::list_alloc(ctx.0.as_ptr(), 0) };
+                self.handle = nonnull_or_alloc_error(empty);
+            }
+        }
+        state.finish(false, ctx)
+    }
+}
+
+impl<'a, T: ListRawAPI + 'a> Extend<T> for List<'a, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for el in iter {
+            self.push(el);
+        }
+    }
+}
+
+impl<'a, T: ListRawAPI + 'a> From<List<'a, T>> for Vec<T> {
+    fn from(list: List<'a, T>) -> Self {
+        list.into_iter().collect()
+    }
+}
+
+impl<'a, T: ListRawAPI + 'a> IntoIterator for List<'a, T> {
+    type Item = T;
+    type IntoIter = IntoIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let back = self.len();
+        IntoIter {
+            list: self,
+            front: 0,
+            back,
+        }
+    }
+}
+
+impl<'u, 'a, T: ListRawAPI + 'a> IntoIterator for &'u List<'a, T> {
+    type Item = T;
+    type IntoIter = Iter<'u, 'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 impl<'a, T: ListRawAPI + 'a> Drop for List<'a, T> {
@@ -408,6 +737,18 @@ impl<'a, T: ListRawAPI + 'a> Clone for List<'a, T> {
     }
 }
 
+impl<'a, T: ListRawAPI + 'a> IntoIsl<List<'a, T>> for List<'a, T> {
+    fn into_isl(self) -> List<'a, T> {
+        self
+    }
+}
+
+impl<'a, T: ListRawAPI + 'a> IntoIsl<List<'a, T>> for &List<'a, T> {
+    fn into_isl(self) -> List<'a, T> {
+        self.clone()
+    }
+}
+
 unsafe impl<'a, T: ListRawAPI + 'a> ISLPrint<'a> for List<'a, T> {
     type Handle = T::ListHandle;
     fn handle(&self) -> *mut Self::Handle {
@@ -427,12 +768,17 @@ impl<'a, T: ListRawAPI + 'a> std::fmt::Debug for List<'a, T> {
 
 pub struct Iter<'a, 'b, T: ListRawAPI + 'a> {
     list: &'a List<'b, T>,
-    index: usize,
+    front: usize,
+    back: usize,
 }
 
 impl<'a, 'b, T: ListRawAPI + 'b> Iter<'a, 'b, T> {
     pub fn new(list: &'a List<'b, T>) -> Self {
-        Self { list, index: 0 }
+        Self {
+            list,
+            front: 0,
+            back: list.len(),
+        }
     }
 }
 
@@ -440,9 +786,9 @@ impl<'a, 'b, T: ListRawAPI + 'b> Iterator for Iter<'a, 'b, T> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.list.len() {
-            let el = self.list.get(self.index);
-            self.index += 1;
+        if self.front < self.back {
+            let el = self.list.get(self.front);
+            self.front += 1;
             el
         } else {
             None
@@ -450,22 +796,65 @@ impl<'a, 'b, T: ListRawAPI + 'b> Iterator for Iter<'a, 'b, T> {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self.list.len() - self.index;
+        let remaining = self.back - self.front;
         (remaining, Some(remaining))
     }
 }
 
 impl<'a, 'b, T: ListRawAPI + 'b> ExactSizeIterator for Iter<'a, 'b, T> {
     fn len(&self) -> usize {
-        self.list.len() - self.index
+        self.back - self.front
     }
 }
 
 impl<'a, 'b, T: ListRawAPI + 'b> DoubleEndedIterator for Iter<'a, 'b, T> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        if self.index > 0 {
-            self.index -= 1;
-            self.list.get(self.index)
+        if self.front < self.back {
+            self.back -= 1;
+            self.list.get(self.back)
+        } else {
+            None
+        }
+    }
+}
+
+/// Owning iterator over a [`List`]; the elements are copied out as they are visited.
+pub struct IntoIter<'a, T: ListRawAPI + 'a> {
+    list: List<'a, T>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T: ListRawAPI + 'a> Iterator for IntoIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            let el = self.list.get(self.front);
+            self.front += 1;
+            el
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T: ListRawAPI + 'a> ExactSizeIterator for IntoIter<'a, T> {
+    fn len(&self) -> usize {
+        self.back - self.front
+    }
+}
+
+impl<'a, T: ListRawAPI + 'a> DoubleEndedIterator for IntoIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.back -= 1;
+            self.list.get(self.back)
         } else {
             None
         }
@@ -476,6 +865,7 @@ impl<'a, 'b, T: ListRawAPI + 'b> DoubleEndedIterator for Iter<'a, 'b, T> {
 mod tests {
     use super::*;
     use crate::Context;
+    use crate::ident::Ident;
     use crate::value::Value;
 
     #[test]
@@ -524,4 +914,69 @@ mod tests {
             assert!(list.get(0).unwrap() == val3);
         });
     }
+
+    #[test]
+    fn test_list_iterators() {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let list = List::from_iter_in(ctx, (1..=4).map(|i| Value::new_si(ctx, i)));
+            assert_eq!(list.len(), 4);
+            let backwards: Vec<i64> = list.iter().rev().map(|v| v.numerator()).collect();
+            assert_eq!(backwards, vec![4, 3, 2, 1]);
+            let mut iter = list.iter();
+            assert_eq!(iter.next().unwrap().numerator(), 1);
+            assert_eq!(iter.next_back().unwrap().numerator(), 4);
+            assert_eq!(iter.len(), 2);
+            let by_ref: Vec<i64> = (&list).into_iter().map(|v| v.numerator()).collect();
+            assert_eq!(by_ref, vec![1, 2, 3, 4]);
+            let owned: Vec<Value> = list.into();
+            assert_eq!(owned.len(), 4);
+            let empty = List::<Value>::from_iter_in(ctx, std::iter::empty());
+            assert!(empty.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_list_editing() {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let values =
+                |list: &List<Value>| list.iter().map(|v| v.numerator()).collect::<Vec<_>>();
+            let mut list = List::from_vec(ctx, vec![Value::new_si(ctx, 1), Value::new_si(ctx, 3)]);
+            list.insert(1, Value::new_si(ctx, 2));
+            list.extend([Value::new_si(ctx, 4), Value::new_si(ctx, 5)]);
+            assert_eq!(values(&list), vec![1, 2, 3, 4, 5]);
+            list.drop_range(1..3);
+            assert_eq!(values(&list), vec![1, 4, 5]);
+            list.swap(0, 2);
+            assert_eq!(values(&list), vec![5, 4, 1]);
+            list.reverse();
+            assert_eq!(values(&list), vec![1, 4, 5]);
+            let list = list.concat(List::new_singleton(Value::new_si(ctx, 0)));
+            assert_eq!(values(&list), vec![1, 4, 5, 0]);
+            let mut list = list;
+            list.sort_by(|a, b| b.numerator().cmp(&a.numerator()))
+                .unwrap();
+            assert_eq!(values(&list), vec![5, 4, 1, 0]);
+            assert_eq!(list.to_vec().len(), 4);
+            list.clear();
+            assert!(list.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_list_of_maps() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let maps = ["{ [i] -> [i + 1] }", "{ [i] -> [2i] }"]
+                .into_iter()
+                .map(|s| crate::map::Map::from_str(ctx, s))
+                .collect::<Result<Vec<_>, _>>()?;
+            let maps = List::from_vec(ctx, maps);
+            assert_eq!(maps.len(), 2);
+            let ids = List::from_iter_in(ctx, [Ident::new(ctx, "a")?, Ident::new(ctx, "b")?]);
+            assert_eq!(ids.get(1).unwrap().name()?, "b");
+            Ok(())
+        })
+    }
 }
@@ -1,20 +1,26 @@
 use std::{mem::ManuallyDrop, ptr::NonNull};
 
-use crate::aff::Affine;
+use crate::aff::{Affine, MultiAffine, PiecewiseMultiAffine};
+use crate::callback::{Callback, IterationControl};
 use crate::ident::Ident;
 use crate::list::List;
 use crate::local_space::LocalSpace;
 use crate::polynomial::PiecewiseQuasiPolynomial;
-use crate::set::Set;
+use crate::set::{BasicSet, Set};
 use crate::space::Space;
 use crate::stat::isl_bool_to_optional_bool;
 use crate::value::Value;
 use crate::{DimType, constraint::Constraint, impl_isl_handle, stat::isl_size_to_optional_u32};
-use crate::{isl_ctor, isl_flag, isl_project, isl_size, isl_str, isl_transform};
+use crate::{
+    isl_ctor, isl_flag, isl_project, isl_size, isl_str, isl_transform, isl_transform_exact,
+};
 
 impl_isl_handle!(Map, map);
 impl_isl_handle!(BasicMap, basic_map);
 
+type ConstraintList<'a> = List<'a, Constraint<'a>>;
+type BasicMapList<'a> = List<'a, BasicMap<'a>>;
+
 impl<'a> TryFrom<Constraint<'a>> for BasicMap<'a> {
     fn try_from(constraint: Constraint<'a>) -> Result<Self, Self::Error> {
         let ctx = constraint.context_ref();
@@ -67,6 +73,94 @@ impl<'a> BasicMap<'a> {
     isl_project!([into(LocalSpace)] get_local_space, isl_basic_map_get_local_space);
     isl_project!([into(Affine)] get_div, isl_basic_map_get_div, [cast(i32)] pos: u32);
     isl_ctor!(from_affine_list, isl_basic_map_from_aff_list, domain_space: Space<'a>, [managed] aff: List<'a, Affine<'a>>);
+    isl_ctor!([ctx] from_str, isl_basic_map_read_from_str, [str] str: &str);
+    isl_ctor!(universe, isl_basic_map_universe, space: Space<'a>);
+    isl_ctor!(empty, isl_basic_map_empty, space: Space<'a>);
+    isl_transform!(intersect, isl_basic_map_intersect, [managed] other: BasicMap<'a>);
+    isl_transform!(intersect_domain, isl_basic_map_intersect_domain, [managed] set: BasicSet<'a>);
+    isl_transform!(intersect_range, isl_basic_map_intersect_range, [managed] set: BasicSet<'a>);
+    isl_transform!(intersect_params, isl_basic_map_intersect_params, [managed] set: BasicSet<'a>);
+    isl_transform!([into(Map)] union, isl_basic_map_union, [managed] other: BasicMap<'a>);
+    isl_transform!(apply_domain, isl_basic_map_apply_domain, [managed] other: BasicMap<'a>);
+    isl_transform!(apply_range, isl_basic_map_apply_range, [managed] other: BasicMap<'a>);
+    isl_transform!(reverse, isl_basic_map_reverse);
+    isl_transform!([into(BasicSet)] domain, isl_basic_map_domain);
+    isl_transform!([into(BasicSet)] range, isl_basic_map_range);
+    isl_transform!([into(BasicSet)] deltas, isl_basic_map_deltas);
+    isl_transform!(affine_hull, isl_basic_map_affine_hull);
+    isl_transform!(sample, isl_basic_map_sample);
+    isl_transform!(detect_equalities, isl_basic_map_detect_equalities);
+    isl_transform!(remove_redundancies, isl_basic_map_remove_redundancies);
+    isl_transform!(gist, isl_basic_map_gist, [managed] context: BasicMap<'a>);
+    isl_transform!([into(Map)] lexmin, isl_basic_map_lexmin);
+    isl_transform!([into(Map)] lexmax, isl_basic_map_lexmax);
+    isl_transform!(fix_si, isl_basic_map_fix_si, dim: Dim, [trivial] value: i32);
+    isl_transform!(project_out, isl_basic_map_project_out, [cast(u32)] dim_type: DimType, [trivial] first: u32, [trivial] num: u32);
+    isl_transform!(add_constraint, isl_basic_map_add_constraint, [managed] constraint: Constraint<'a>);
+    isl_project!([into(ConstraintList)] get_constraints, isl_basic_map_get_constraint_list);
+    isl_size!(basic_map_n_constraint => num_constraints);
+    isl_flag!(basic_map_is_equal => checked_eq, [ref] other: &BasicMap<'a>);
+    isl_flag!(basic_map_is_disjoint => disjoint, [ref] other: &BasicMap<'a>);
+    isl_flag!(basic_map_is_subset => subset, [ref] other: &BasicMap<'a>);
+    isl_flag!(basic_map_is_empty => is_empty);
+    isl_flag!(basic_map_is_universe => is_universe);
+
+    /// Visit the constraints of the basic map.
+    pub fn foreach_constraint<F, R>(&self, func: F) -> Result<(), crate::Error>
+    where
+        F: FnMut(Constraint<'a>) -> Result<R, crate::Error>,
+        R: IterationControl,
+    {
+        unsafe extern "C" fn callback<'a, F, R>(
+            constraint: *mut barvinok_sys::isl_constraint,
+            user: *mut std::ffi::c_void,
+        ) -> barvinok_sys::isl_stat
+        where
+            F: FnMut(Constraint<'a>) -> Result<R, crate::Error>,
+            R: IterationControl,
+        {
+            let Some(constraint) = NonNull::new(constraint) else {
+                return barvinok_sys::isl_stat_isl_stat_error;
+            };
+            let state = unsafe { Callback::<F>::from_user(user) };
+            let constraint = Constraint {
+                handle: constraint,
+                marker: std::marker::PhantomData,
+            };
+            state.run_stat(|func| func(constraint))
+        }
+        let mut state = Callback::new(func);
+        let res = unsafe {
+            barvinok_sys::isl_basic_map_foreach_constraint(
+                self.handle.as_ptr(),
+                Some(callback::<F, R>),
+                state.as_user(),
+            )
+        };
+        state.finish(
+            res != barvinok_sys::isl_stat_isl_stat_ok,
+            self.context_ref(),
+        )
+    }
+}
+
+impl PartialEq for BasicMap<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.checked_eq(other).unwrap_or(false)
+    }
+}
+
+impl<'a> List<'a, BasicMap<'a>> {
+    pub fn intersect(self) -> Result<BasicMap<'a>, crate::Error> {
+        let ctx = self.context();
+        let this = ManuallyDrop::new(self);
+        let handle = unsafe { barvinok_sys::isl_basic_map_list_intersect(this.handle.as_ptr()) };
+        let handle = NonNull::new(handle).ok_or_else(|| ctx.last_error_or_unknown())?;
+        Ok(BasicMap {
+            handle,
+            marker: std::marker::PhantomData,
+        })
+    }
 }
 
 #[allow(clippy::should_implement_trait)]
@@ -75,6 +169,7 @@ impl<'a> Map<'a> {
     isl_size!(map_range_tuple_dim => range_tuple_dim);
     isl_size!(map_dim => dim, [cast(u32)] dim_type: DimType);
     isl_project!([into(Space)] get_space, isl_map_get_space);
+    isl_ctor!([ctx] from_str, isl_map_read_from_str, [str] str : &str);
     isl_ctor!(lex_lt, isl_map_lex_lt, space: Space<'a>);
     isl_ctor!(lex_le, isl_map_lex_le, space: Space<'a>);
     isl_ctor!(lex_ge, isl_map_lex_ge, space: Space<'a>);
@@ -134,15 +229,19 @@ impl<'a> Map<'a> {
     isl_transform!(subtract_range, isl_map_subtract_range, [managed] set: Set<'a>);
     isl_transform!(complement, isl_map_complement);
     isl_transform!(fix_input_si, isl_map_fix_input_si, [trivial] input: u32, [trivial] value: i32);
-    isl_transform!(fix_si, isl_map_fix_si, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [trivial] value: i32);
-    isl_transform!(fix_val, isl_map_fix_val, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [managed] value: Value<'a>);
-    isl_transform!(lower_bound_si, isl_map_lower_bound_si, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [trivial] value: i32);
-    isl_transform!(lower_bound_val, isl_map_lower_bound_val, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [managed] value: Value<'a>);
-    isl_transform!(upper_bound_si, isl_map_upper_bound_si, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [trivial] value: i32);
-    isl_transform!(upper_bound_val, isl_map_upper_bound_val, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [managed] value: Value<'a>);
+    isl_transform!(fix_si, isl_map_fix_si, dim: Dim, [trivial] value: i32);
+    isl_transform!(fix_val, isl_map_fix_val, dim: Dim, [managed] value: Value<'a>);
+    isl_transform!(lower_bound_si, isl_map_lower_bound_si, dim: Dim, [trivial] value: i32);
+    isl_transform!(lower_bound_val, isl_map_lower_bound_val, dim: Dim, [managed] value: Value<'a>);
+    isl_transform!(upper_bound_si, isl_map_upper_bound_si, dim: Dim, [trivial] value: i32);
+    isl_transform!(upper_bound_val, isl_map_upper_bound_val, dim: Dim, [managed] value: Value<'a>);
     isl_transform!([into(Set)] deltas, isl_map_deltas);
     isl_transform!(deltas_map, isl_map_deltas_map);
     isl_transform!(detect_equalities, isl_map_detect_equalities);
+    isl_transform!(fixed_power_val, isl_map_fixed_power_val, [managed] exp: Value<'a>);
+    isl_transform_exact!(transitive_closure, isl_map_transitive_closure);
+    isl_transform_exact!(power, isl_map_power);
+    isl_transform_exact!(reaching_path_lengths, isl_map_reaching_path_lengths);
     isl_transform!(add_dims, isl_map_add_dims, [cast(u32)] dim_type: DimType, [trivial] num: u32);
     isl_transform!(insert_dims, isl_map_insert_dims, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [trivial] num: u32);
     isl_transform!(move_dims, isl_map_move_dims, [cast(u32)] dim_type: DimType, [cast(u32)] dst_type: DimType, [trivial] dst_pos: u32, [trivial] src_pos: u32, [trivial] num: u32);
@@ -160,19 +259,98 @@ impl<'a> Map<'a> {
     isl_transform!(add_constraint, isl_map_add_constraint, [managed] constraint: Constraint<'a>);
     isl_transform!([into(Set)] domain, isl_map_domain);
     isl_transform!([into(Set)] range, isl_map_range);
-    isl_transform!(set_dim_name, isl_map_set_dim_name, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [str] name: &str);
-    isl_flag!(map_has_dim_name => has_dim_name, [cast(u32)] dim_type: DimType, [trivial] pos: u32);
-    isl_str!(map_get_dim_name => get_dim_name, [cast(u32)] dim_type: DimType, [trivial] pos: u32);
+    isl_transform!(set_dim_name, isl_map_set_dim_name, dim: Dim, [str] name: &str);
+    isl_flag!(map_has_dim_name => has_dim_name, dim: Dim);
+    isl_str!(map_get_dim_name => get_dim_name, dim: Dim);
+    isl_flag!(map_is_single_valued => is_single_valued);
+    isl_flag!(map_is_injective => is_injective);
+    isl_flag!(map_is_bijective => is_bijective);
+    isl_flag!(map_is_identity => is_identity);
+    isl_flag!(map_is_translation => is_translation);
+    isl_flag!(map_is_empty => is_empty);
+    isl_flag!(map_plain_is_universe => plain_is_universe);
+    isl_flag!(map_is_subset => subset, [ref] other: &Self);
+    isl_flag!(map_is_strict_subset => strict_subset, [ref] other: &Self);
+    isl_ctor!(from_domain_and_range, isl_map_from_domain_and_range, domain: Set<'a>, [managed] range: Set<'a>);
+    isl_transform!(domain_map, isl_map_domain_map);
+    isl_transform!(range_map, isl_map_range_map);
+    isl_transform!(curry, isl_map_curry);
+    isl_transform!(uncurry, isl_map_uncurry);
+    isl_transform!(zip, isl_map_zip);
+    isl_transform!([into(Set)] wrap, isl_map_wrap);
+    isl_transform!(preimage_domain_multi_aff, isl_map_preimage_domain_multi_aff, [managed] ma: MultiAffine<'a>);
+    isl_transform!(preimage_range_multi_aff, isl_map_preimage_range_multi_aff, [managed] ma: MultiAffine<'a>);
+    isl_transform!(preimage_domain_pw_multi_aff, isl_map_preimage_domain_pw_multi_aff, [managed] pma: PiecewiseMultiAffine<'a>);
+    isl_transform!(preimage_range_pw_multi_aff, isl_map_preimage_range_pw_multi_aff, [managed] pma: PiecewiseMultiAffine<'a>);
+    isl_transform!([into(PiecewiseMultiAffine)] as_pw_multi_aff, isl_map_as_pw_multi_aff);
+
+    pub fn fixed_power(self, exp: i64) -> Result<Self, crate::Error> {
+        let exp = Value::new_si(self.context_ref(), exp);
+        self.fixed_power_val(exp)
+    }
+    isl_size!(map_n_basic_map => num_basic_maps);
+    isl_project!([into(BasicMapList)] get_basic_map_list, isl_map_get_basic_map_list);
+
+    /// Visit the disjuncts of the map.
+    pub fn foreach_basic_map<F, R>(&self, func: F) -> Result<(), crate::Error>
+    where
+        F: FnMut(BasicMap<'a>) -> Result<R, crate::Error>,
+        R: IterationControl,
+    {
+        unsafe extern "C" fn callback<'a, F, R>(
+            basic_map: *mut barvinok_sys::isl_basic_map,
+            user: *mut std::ffi::c_void,
+        ) -> barvinok_sys::isl_stat
+        where
+            F: FnMut(BasicMap<'a>) -> Result<R, crate::Error>,
+            R: IterationControl,
+        {
+            let Some(basic_map) = NonNull::new(basic_map) else {
+                return barvinok_sys::isl_stat_isl_stat_error;
+            };
+            let state = unsafe { Callback::<F>::from_user(user) };
+            let basic_map = BasicMap {
+                handle: basic_map,
+                marker: std::marker::PhantomData,
+            };
+            state.run_stat(|func| func(basic_map))
+        }
+        let mut state = Callback::new(func);
+        let res = unsafe {
+            barvinok_sys::isl_map_foreach_basic_map(
+                self.handle.as_ptr(),
+                Some(callback::<F, R>),
+                state.as_user(),
+            )
+        };
+        state.finish(
+            res != barvinok_sys::isl_stat_isl_stat_ok,
+            self.context_ref(),
+        )
+    }
+}
+
+/// Semantic equality: both sides represent the same relation. Errors, such as
+/// mismatched spaces, compare unequal. [`PlainEq`](crate::plain::PlainEq) gives a cheaper
+/// structural equality with a matching hash and order.
+impl PartialEq for Map<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.equal(other).unwrap_or(false)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         Context, DimType,
+        aff::{MultiAffine, PiecewiseMultiAffine},
         constraint::Constraint,
+        list::List,
         local_space::LocalSpace,
         map::{BasicMap, Map},
+        set::{BasicSet, Set},
         space::Space,
+        value::Value,
     };
 
     #[test]
@@ -199,4 +377,221 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn test_transitive_closure() -> anyhow::Result<()> {
+        let context = Context::new();
+        context.scope(|context| {
+            let step = Map::from_str(context, "[n] -> { [i] -> [i + 1] : 0 <= i < n }")?;
+            let (closure, exact) = step.clone().transitive_closure()?;
+            assert!(exact);
+            let expected = Map::from_str(context, "[n] -> { [i] -> [j] : 0 <= i < j <= n }")?;
+            assert!(closure.equal(&expected)?);
+
+            // every j is reached by paths of length 1 to j
+            let (lengths, exact) = step.clone().reaching_path_lengths()?;
+            assert!(exact);
+            let expected = Map::from_str(context, "[n] -> { [j] -> [k] : 0 < k <= j <= n }")?;
+            assert!(lengths.equal(&expected)?);
+
+            let (power, exact) = step.power()?;
+            assert!(exact);
+            let expected = Map::from_str(
+                context,
+                "[n] -> { [k] -> [[i] -> [i + k]] : k > 0 and 0 <= i <= n - k }",
+            )?;
+            assert!(power.equal(&expected)?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_fixed_power_val() -> anyhow::Result<()> {
+        let context = Context::new();
+        context.scope(|context| {
+            let step = Map::from_str(context, "{ [i] -> [i + 2] }")?;
+            let cubed = step.fixed_power_val(Value::new_si(context, 3))?;
+            let expected = Map::from_str(context, "{ [i] -> [i + 6] }")?;
+            assert!(cubed.equal(&expected)?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_basic_map_operations() -> anyhow::Result<()> {
+        let context = Context::new();
+        context.scope(|context| {
+            let bmap = BasicMap::from_str(context, "{ [i] -> [j] : 0 <= i < 10 and j = i + 1 }")?;
+            let shift = BasicMap::from_str(context, "{ [j] -> [k] : k = 2j }")?;
+            let composed = bmap.clone().apply_range(shift)?;
+            let expected =
+                BasicMap::from_str(context, "{ [i] -> [k] : 0 <= i < 10 and k = 2i + 2 }")?;
+            assert_eq!(composed, expected);
+            let domain = bmap.clone().domain()?;
+            assert_eq!(
+                domain,
+                BasicSet::from_str(context, "{ [i] : 0 <= i < 10 }")?
+            );
+            let range = bmap.clone().reverse()?.domain()?;
+            assert_eq!(range, bmap.clone().range()?);
+            let restricted = bmap
+                .clone()
+                .intersect(BasicMap::from_str(context, "{ [i] -> [j] : i >= 5 }")?)?;
+            let lexmin = restricted.clone().lexmin()?;
+            assert!(lexmin.equal(&Map::try_from(restricted.clone())?)?);
+            assert_eq!(
+                restricted.clone().range()?.lexmin()?,
+                Set::from_str(context, "{ [6] }")?
+            );
+            assert!(!restricted.clone().sample()?.is_empty()?);
+            assert!(restricted.subset(&bmap)?);
+            let hull = bmap.affine_hull()?;
+            assert_eq!(hull, BasicMap::from_str(context, "{ [i] -> [i + 1] }")?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_basic_map_constraints() -> anyhow::Result<()> {
+        let context = Context::new();
+        context.scope(|context| {
+            let bmap = BasicMap::from_str(context, "{ [i] -> [j] : 0 <= i < 10 and j = i + 1 }")?;
+            let num = bmap.num_constraints()?;
+            assert_eq!(bmap.get_constraints()?.len(), num as usize);
+            let mut visited = 0;
+            bmap.foreach_constraint(|_| {
+                visited += 1;
+                Ok(())
+            })?;
+            assert_eq!(visited, num);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_foreach_basic_map() -> anyhow::Result<()> {
+        let context = Context::new();
+        context.scope(|context| {
+            let map = Map::from_str(
+                context,
+                "{ [i] -> [i + 1] : i < 0; [i] -> [i - 1] : i > 10 }",
+            )?;
+            assert_eq!(map.num_basic_maps()?, 2);
+            let mut pieces = Vec::new();
+            map.foreach_basic_map(|bmap| {
+                pieces.push(bmap);
+                Ok(())
+            })?;
+            assert_eq!(pieces.len(), 2);
+            let list = map.get_basic_map_list()?;
+            assert_eq!(list.len(), 2);
+            let rebuilt = pieces
+                .into_iter()
+                .map(Map::try_from)
+                .try_fold(Map::empty(map.get_space()?)?, |acc, piece| {
+                    acc.union(piece?)
+                })?;
+            assert!(rebuilt.equal(&map)?);
+            let err = map.foreach_basic_map(|_| Err::<(), _>(crate::Error::ParseError));
+            assert!(matches!(err, Err(crate::Error::ParseError)));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_basic_map_list_intersect() -> anyhow::Result<()> {
+        let context = Context::new();
+        context.scope(|context| {
+            let mut list = List::new(context, 2);
+            list.push(BasicMap::from_str(context, "{ [i] -> [j] : j >= i }")?);
+            list.push(BasicMap::from_str(context, "{ [i] -> [j] : j <= i }")?);
+            let identity = list.intersect()?;
+            assert_eq!(identity, BasicMap::from_str(context, "{ [i] -> [i] }")?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_access_properties() -> anyhow::Result<()> {
+        let context = Context::new();
+        context.scope(|context| {
+            let access = Map::from_str(context, "{ S[i, j] -> A[i + j] : 0 <= i, j < 4 }")?;
+            assert!(access.is_single_valued()?);
+            assert!(!access.is_injective()?);
+            assert!(!access.is_bijective()?);
+            let transpose = Map::from_str(context, "{ S[i, j] -> A[j, i] }")?;
+            assert!(transpose.is_bijective()?);
+            assert!(!transpose.is_identity()?);
+            assert!(Map::from_str(context, "{ S[i] -> S[i] }")?.is_identity()?);
+            assert!(Map::from_str(context, "{ [i] -> [i + 3] }")?.is_translation()?);
+            let restricted = access
+                .clone()
+                .intersect_domain(Set::from_str(context, "{ S[i, j] : i = j }")?)?;
+            assert!(restricted.subset(&access)?);
+            assert!(restricted.strict_subset(&access)?);
+            assert!(!restricted.is_empty()?);
+            let pma = transpose.as_pw_multi_aff()?;
+            assert!(
+                pma.to_map()?
+                    .equal(&Map::from_str(context, "{ S[i, j] -> A[j, i] }")?)?
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_restructuring() -> anyhow::Result<()> {
+        let context = Context::new();
+        context.scope(|context| {
+            let map = Map::from_str(context, "{ [[i] -> [j]] -> [k] : k = i + j }")?;
+            let curried = map.clone().curry()?;
+            assert!(curried.range_is_wrapping()?);
+            assert!(curried.clone().uncurry()?.equal(&map)?);
+            let zipped = Map::from_str(context, "{ [[a] -> [b]] -> [[c] -> [d]] }")?.zip()?;
+            assert!(zipped.domain_is_wrapping()?);
+            let wrapped = map.clone().wrap()?;
+            assert!(wrapped.is_wrapping()?);
+            assert!(wrapped.unwrap()?.equal(&map)?);
+
+            let simple = Map::from_str(context, "{ [i] -> [i + 1] }")?;
+            let domain_map = simple.clone().domain_map()?;
+            assert!(domain_map.domain_is_wrapping()?);
+            assert_eq!(domain_map.range_tuple_dim()?, 1);
+            assert!(simple.clone().range_map()?.domain_is_wrapping()?);
+
+            let product = Map::from_domain_and_range(
+                Set::from_str(context, "{ [i] : 0 <= i < 2 }")?,
+                Set::from_str(context, "{ [j] : 0 <= j < 3 }")?,
+            )?;
+            assert_eq!(
+                product.domain()?,
+                Set::from_str(context, "{ [i] : 0 <= i < 2 }")?
+            );
+            assert!(
+                simple
+                    .fixed_power(4)?
+                    .equal(&Map::from_str(context, "{ [i] -> [i + 4] }")?)?
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_preimage() -> anyhow::Result<()> {
+        let context = Context::new();
+        context.scope(|context| {
+            let access = Map::from_str(context, "{ S[i] -> A[i] : 0 <= i < 8 }")?;
+            let tile = MultiAffine::from_str(context, "{ T[t, p] -> S[4t + p] }")?;
+            let tiled = access.clone().preimage_domain_multi_aff(tile.clone())?;
+            let expected = Map::from_str(context, "{ T[t, p] -> A[4t + p] : 0 <= 4t + p < 8 }")?;
+            assert!(tiled.equal(&expected)?);
+            let layout = MultiAffine::from_str(context, "{ B[x] -> A[2x] }")?;
+            let remapped = access.clone().preimage_range_multi_aff(layout)?;
+            let expected = Map::from_str(context, "{ S[i] -> B[x] : i = 2x and 0 <= i < 8 }")?;
+            assert!(remapped.equal(&expected)?);
+            let pma = PiecewiseMultiAffine::from_str(context, "{ T[t, p] -> S[4t + p] }")?;
+            assert!(access.preimage_domain_pw_multi_aff(pma)?.equal(&tiled)?);
+            Ok(())
+        })
+    }
 }
@@ -0,0 +1,424 @@
+//! Arithmetic operators for affine expressions and quasi-polynomials.
+//!
+//! ISL operations can fail, so every operator returns `Result<T, Error>` instead of
+//! panicking. A `Result` can be fed back into another operator to build an expression
+//! and the error is only checked at the end, e.g. `((a + b) - c)?`, or checked early
+//! with `?` to continue with scalars, e.g. `(a + b)? * 2`.
+//!
+//! Scalars are accepted as [`Value`] or `i64` on the right-hand side:
+//! - `+` and `-` add a constant to the expression, keeping its domain;
+//! - `*` and `/` scale the expression up or down.
+//!
+//! Sets and maps overload the set-algebra operators on owned values and on references:
+//! `|` (union), `&` (intersection), `-` (difference), `!` (complement), `<=` (subset)
+//! and, for maps, `>>` ([`Map::apply_range`]). Like the arithmetic operators they return
+//! `Result`, so `((&a | &b) - c)?` checks the error once at the end. The `try_` methods
+//! (e.g. [`Set::try_union`]) do the same with both operands borrowed.
+use std::cmp::Ordering;
+use std::ops::{Add, BitAnd, BitOr, Div, Mul, Neg, Not, Shr, Sub};
+
+use crate::{
+    Error,
+    aff::{Affine, PiecewiseAffine},
+    map::Map,
+    polynomial::{PiecewiseQuasiPolynomial, QuasiPolynomial},
+    set::{BasicSet, Set},
+    value::Value,
+};
+
+macro_rules! impl_checked_binop {
+    ($type:ident, $trait:ident, $method:ident, $checked:ident) => {
+        impl<'a> $trait for $type<'a> {
+            type Output = Result<$type<'a>, Error>;
+            fn $method(self, rhs: $type<'a>) -> Self::Output {
+                self.$checked(rhs)
+            }
+        }
+
+        impl<'a> $trait<$type<'a>> for Result<$type<'a>, Error> {
+            type Output = Result<$type<'a>, Error>;
+            fn $method(self, rhs: $type<'a>) -> Self::Output {
+                self?.$checked(rhs)
+            }
+        }
+
+        impl<'a> $trait<Result<$type<'a>, Error>> for $type<'a> {
+            type Output = Result<$type<'a>, Error>;
+            fn $method(self, rhs: Result<$type<'a>, Error>) -> Self::Output {
+                self.$checked(rhs?)
+            }
+        }
+    };
+}
+
+macro_rules! impl_checked_neg {
+    ($type:ident) => {
+        impl<'a> Neg for $type<'a> {
+            type Output = Result<$type<'a>, Error>;
+            fn neg(self) -> Self::Output {
+                self.checked_neg()
+            }
+        }
+    };
+}
+
+/// `$body` maps `(self, value)` to the result, where `value` is a [`Value`].
+/// The `i64` variant converts the scalar in the context of `self` first.
+macro_rules! impl_scalar_op {
+    ($type:ident, $trait:ident, $method:ident, $inherent:ident) => {
+        impl_scalar_op!($type, $trait, $method, |this, val| this.$inherent(val));
+    };
+    ($type:ident, $trait:ident, $method:ident, |$this:ident, $val:ident| $body:expr) => {
+        impl<'a> $trait<Value<'a>> for $type<'a> {
+            type Output = Result<$type<'a>, Error>;
+            fn $method(self, rhs: Value<'a>) -> Self::Output {
+                let $this = self;
+                let $val = rhs;
+                $body
+            }
+        }
+
+        impl<'a> $trait<i64> for $type<'a> {
+            type Output = Result<$type<'a>, Error>;
+            fn $method(self, rhs: i64) -> Self::Output {
+                let rhs = Value::new_si(self.context_ref(), rhs);
+                $trait::$method(self, rhs)
+            }
+        }
+    };
+}
+
+impl_checked_binop!(Affine, Add, add, checked_add);
+impl_checked_binop!(Affine, Sub, sub, checked_sub);
+impl_checked_binop!(Affine, Mul, mul, checked_mul);
+impl_checked_binop!(Affine, Div, div, checked_div);
+impl_checked_neg!(Affine);
+impl_scalar_op!(Affine, Add, add, add_constant_val);
+impl_scalar_op!(Affine, Sub, sub, |this, val| {
+    this.add_constant_val(-val)
+});
+impl_scalar_op!(Affine, Mul, mul, scale_val);
+impl_scalar_op!(Affine, Div, div, scale_down_val);
+
+impl_checked_binop!(PiecewiseAffine, Add, add, checked_add);
+impl_checked_binop!(PiecewiseAffine, Sub, sub, checked_sub);
+impl_checked_binop!(PiecewiseAffine, Mul, mul, checked_mul);
+impl_checked_binop!(PiecewiseAffine, Div, div, checked_div);
+impl_checked_neg!(PiecewiseAffine);
+impl_scalar_op!(PiecewiseAffine, Add, add, add_constant_val);
+impl_scalar_op!(PiecewiseAffine, Sub, sub, |this, val| {
+    this.add_constant_val(-val)
+});
+impl_scalar_op!(PiecewiseAffine, Mul, mul, scale_val);
+impl_scalar_op!(PiecewiseAffine, Div, div, scale_down_val);
+
+impl_checked_binop!(QuasiPolynomial, Add, add, checked_add);
+impl_checked_binop!(QuasiPolynomial, Sub, sub, checked_sub);
+impl_checked_binop!(QuasiPolynomial, Mul, mul, checked_mul);
+impl_scalar_op!(QuasiPolynomial, Add, add, |this, val| {
+    let constant = QuasiPolynomial::val_on_domain(this.get_domain_space()?, val)?;
+    this.checked_add(constant)
+});
+impl_scalar_op!(QuasiPolynomial, Sub, sub, |this, val| this + (-val));
+impl_scalar_op!(QuasiPolynomial, Mul, mul, scale_up_val);
+impl_scalar_op!(QuasiPolynomial, Div, div, scale_down_val);
+
+impl_checked_binop!(PiecewiseQuasiPolynomial, Add, add, checked_add);
+impl_checked_binop!(PiecewiseQuasiPolynomial, Sub, sub, checked_sub);
+impl_checked_binop!(PiecewiseQuasiPolynomial, Mul, mul, checked_mul);
+impl_checked_neg!(PiecewiseQuasiPolynomial);
+// The sum of two piecewise quasi-polynomials is defined on the union of their domains,
+// so the constant is restricted to the domain of `self` before it is added.
+impl_scalar_op!(PiecewiseQuasiPolynomial, Add, add, |this, val| {
+    let constant = QuasiPolynomial::val_on_domain(this.get_domain_space()?, val)?;
+    let constant =
+        PiecewiseQuasiPolynomial::try_from(constant)?.intersect_domain(this.clone().domain()?)?;
+    this.checked_add(constant)
+});
+impl_scalar_op!(PiecewiseQuasiPolynomial, Sub, sub, |this, val| {
+    this + (-val)
+});
+impl_scalar_op!(PiecewiseQuasiPolynomial, Mul, mul, scale_val);
+impl_scalar_op!(PiecewiseQuasiPolynomial, Div, div, scale_down_val);
+
+/// Implements a set-algebra operator on top of the consuming method `$op`, together with
+/// the `$try_fn` method that borrows both operands.
+macro_rules! impl_set_binop {
+    ($type:ident, $trait:ident, $method:ident, $op:ident, $try_fn:ident => $out:ident) => {
+        impl<'a> $type<'a> {
+            #[doc = concat!("Borrowing variant of [`", stringify!($type), "::", stringify!($op), "`].")]
+            pub fn $try_fn(&self, other: &$type<'a>) -> Result<$out<'a>, Error> {
+                self.clone().$op(other.clone())
+            }
+        }
+
+        impl<'a> $trait for $type<'a> {
+            type Output = Result<$out<'a>, Error>;
+            fn $method(self, rhs: $type<'a>) -> Self::Output {
+                self.$op(rhs)
+            }
+        }
+
+        impl<'a> $trait<&$type<'a>> for $type<'a> {
+            type Output = Result<$out<'a>, Error>;
+            fn $method(self, rhs: &$type<'a>) -> Self::Output {
+                self.$op(rhs.clone())
+            }
+        }
+
+        impl<'a> $trait<$type<'a>> for &$type<'a> {
+            type Output = Result<$out<'a>, Error>;
+            fn $method(self, rhs: $type<'a>) -> Self::Output {
+                self.clone().$op(rhs)
+            }
+        }
+
+        impl<'a> $trait for &$type<'a> {
+            type Output = Result<$out<'a>, Error>;
+            fn $method(self, rhs: &$type<'a>) -> Self::Output {
+                self.$try_fn(rhs)
+            }
+        }
+
+        impl<'a> $trait<$type<'a>> for Result<$type<'a>, Error> {
+            type Output = Result<$out<'a>, Error>;
+            fn $method(self, rhs: $type<'a>) -> Self::Output {
+                self?.$op(rhs)
+            }
+        }
+
+        impl<'a> $trait<Result<$type<'a>, Error>> for $type<'a> {
+            type Output = Result<$out<'a>, Error>;
+            fn $method(self, rhs: Result<$type<'a>, Error>) -> Self::Output {
+                self.$op(rhs?)
+            }
+        }
+    };
+}
+
+macro_rules! impl_set_not {
+    ($type:ident => $out:ident) => {
+        impl<'a> $type<'a> {
+            #[doc = concat!("Borrowing variant of [`", stringify!($type), "::complement`].")]
+            pub fn try_complement(&self) -> Result<$out<'a>, Error> {
+                self.clone().complement()
+            }
+        }
+
+        impl<'a> Not for $type<'a> {
+            type Output = Result<$out<'a>, Error>;
+            fn not(self) -> Self::Output {
+                self.complement()
+            }
+        }
+
+        impl<'a> Not for &$type<'a> {
+            type Output = Result<$out<'a>, Error>;
+            fn not(self) -> Self::Output {
+                self.try_complement()
+            }
+        }
+    };
+}
+
+/// Sets are partially ordered by inclusion. Incomparable sets, and sets that ISL fails
+/// to compare, yield `None`, so all the comparison operators return `false` for them.
+macro_rules! impl_subset_order {
+    ($type:ident) => {
+        impl PartialOrd for $type<'_> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                match (self.subset(other).ok()?, other.subset(self).ok()?) {
+                    (true, true) => Some(Ordering::Equal),
+                    (true, false) => Some(Ordering::Less),
+                    (false, true) => Some(Ordering::Greater),
+                    (false, false) => None,
+                }
+            }
+
+            fn le(&self, other: &Self) -> bool {
+                self.subset(other).unwrap_or(false)
+            }
+
+            fn ge(&self, other: &Self) -> bool {
+                other.subset(self).unwrap_or(false)
+            }
+        }
+    };
+}
+
+impl_set_binop!(Set, BitOr, bitor, union, try_union => Set);
+impl_set_binop!(Set, BitAnd, bitand, intersect, try_intersect => Set);
+impl_set_binop!(Set, Sub, sub, subtract, try_subtract => Set);
+impl_set_not!(Set => Set);
+impl_subset_order!(Set);
+
+impl_set_binop!(BasicSet, BitOr, bitor, union, try_union => Set);
+impl_set_binop!(BasicSet, BitAnd, bitand, intersect, try_intersect => BasicSet);
+impl_set_binop!(BasicSet, Sub, sub, subtract, try_subtract => Set);
+impl_set_not!(BasicSet => Set);
+impl_subset_order!(BasicSet);
+
+impl_set_binop!(Map, BitOr, bitor, union, try_union => Map);
+impl_set_binop!(Map, BitAnd, bitand, intersect, try_intersect => Map);
+impl_set_binop!(Map, Sub, sub, subtract, try_subtract => Map);
+impl_set_binop!(Map, Shr, shr, apply_range, try_apply_range => Map);
+impl_set_not!(Map => Map);
+impl_subset_order!(Map);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, DimType, set::Set};
+
+    #[test]
+    fn test_affine_operators() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let i = Affine::from_str(ctx, "{ [i, j] -> [(i)] }")?;
+            let j = Affine::from_str(ctx, "{ [i, j] -> [(j)] }")?;
+            let expr = ((i.clone() + j.clone()) - i.clone())?;
+            assert!(expr.plain_is_equal(&j)?);
+            let scaled = ((i.clone() + j)? * 6)? / 3;
+            let expected = Affine::from_str(ctx, "{ [i, j] -> [(2i + 2j)] }")?;
+            assert!(scaled?.plain_is_equal(&expected)?);
+            let shifted = ((i.clone() + 5)? - Value::new_si(ctx, 2))?;
+            let expected = Affine::from_str(ctx, "{ [i, j] -> [(3 + i)] }")?;
+            assert!(shifted.plain_is_equal(&expected)?);
+            let expected = Affine::from_str(ctx, "{ [i, j] -> [(-i)] }")?;
+            assert!((-i)?.plain_is_equal(&expected)?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_affine_div_is_division() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let lhs = Affine::from_str(ctx, "{ [i] -> [(4i)] }")?;
+            let rhs = Affine::from_str(ctx, "{ [i] -> [(2)] }")?;
+            let expected = Affine::from_str(ctx, "{ [i] -> [(2i)] }")?;
+            assert!((lhs / rhs)?.plain_is_equal(&expected)?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_error_propagates() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let lhs = Affine::from_str(ctx, "{ [i] -> [(i)] }")?;
+            let rhs = Affine::from_str(ctx, "{ [i, j] -> [(j)] }")?;
+            let third = Affine::from_str(ctx, "{ [i] -> [(1)] }")?;
+            assert!(((lhs + rhs) + third).is_err());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_pw_aff_operators() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let pa = PiecewiseAffine::from_str(ctx, "{ [i] -> [(i)] : i >= 0 }")?;
+            let result = ((pa.clone() * 3)? + pa)? - 1;
+            let expected = PiecewiseAffine::from_str(ctx, "{ [i] -> [(4i - 1)] : i >= 0 }")?;
+            assert_eq!(result?, expected);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_polynomial_scalars() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let domain = Set::from_str(ctx, "[n] -> { [i] }")?.get_space()?;
+            let n = QuasiPolynomial::var_on_domain(domain.clone(), DimType::Param, 0)?;
+            let qp = ((n.clone() * Value::new_si(ctx, 4))? + 2)? / 2;
+            let expected = ((n.clone() + n.clone())? + 1)?;
+            assert!(qp?.plain_is_equal(&expected)?);
+
+            let restricted = PiecewiseQuasiPolynomial::try_from(n)?
+                .intersect_domain(Set::from_str(ctx, "[n] -> { [i] : 0 <= i < n }")?)?;
+            let shifted = (restricted.clone() + 1)?;
+            assert_eq!(shifted.clone().domain()?, restricted.clone().domain()?);
+            let back = ((shifted - 1)? * 3)? / 3;
+            assert!(back?.plain_is_equal(&restricted)?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_set_operators() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let a = Set::from_str(ctx, "{ [i] : 0 <= i < 10 }")?;
+            let b = Set::from_str(ctx, "{ [i] : 5 <= i < 15 }")?;
+            assert_eq!((&a | &b)?, Set::from_str(ctx, "{ [i] : 0 <= i < 15 }")?);
+            assert_eq!((&a & &b)?, Set::from_str(ctx, "{ [i] : 5 <= i < 10 }")?);
+            assert_eq!((&a - &b)?, Set::from_str(ctx, "{ [i] : 0 <= i < 5 }")?);
+            assert_eq!(a.try_union(&b)?, (a.clone() | b.clone())?);
+            let outside = (!&a)?;
+            assert!((&outside & &a)?.is_empty()?);
+            assert_eq!((!outside)?, a);
+
+            let c = (&a & &b)?;
+            assert!(c <= a);
+            assert!(c < a);
+            assert!(a >= c);
+            assert_eq!(a.partial_cmp(&b), None);
+            assert_eq!(a.partial_cmp(&a.clone()), Some(Ordering::Equal));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_set_error_propagates() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let a = Set::from_str(ctx, "{ [i] : 0 <= i < 10 }")?;
+            let b = Set::from_str(ctx, "{ [i, j] : 0 <= i, j < 10 }")?;
+            assert!((&a | &b).is_err());
+            assert!(((a.clone() | b) - a.clone()).is_err());
+            let chained = ((&a - &a) | a.clone())?;
+            assert_eq!(chained, a);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_basic_set_operators() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let a = BasicSet::from_str(ctx, "{ [i] : 0 <= i < 10 }")?;
+            let b = BasicSet::from_str(ctx, "{ [i] : 3 <= i < 5 }")?;
+            assert_eq!((&a & &b)?, b);
+            assert!(b <= a);
+            let holes = (&a - &b)?;
+            assert_eq!(
+                holes,
+                Set::from_str(ctx, "{ [i] : 0 <= i < 3 or 5 <= i < 10 }")?
+            );
+            assert_eq!((&a | &b)?, Set::try_from(a.clone())?);
+            assert!((!&a)?.try_intersect(&Set::try_from(a)?)?.is_empty()?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_map_operators() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let shift = Map::from_str(ctx, "{ [i] -> [i + 1] }")?;
+            let double = Map::from_str(ctx, "{ [i] -> [2i] }")?;
+            assert_eq!(
+                (&shift >> &double)?,
+                Map::from_str(ctx, "{ [i] -> [2i + 2] }")?
+            );
+            assert_eq!(shift.try_apply_range(&double)?, (shift.clone() >> double)?);
+            let small = Map::from_str(ctx, "{ [i] -> [i + 1] : 0 <= i < 4 }")?;
+            assert!(small <= shift);
+            assert!((&shift - &small)?.try_intersect(&small)?.is_empty()?);
+            assert_eq!((&small | &shift)?, shift);
+            Ok(())
+        })
+    }
+}
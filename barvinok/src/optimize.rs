@@ -0,0 +1,215 @@
+use std::{mem::ManuallyDrop, ptr::NonNull};
+
+use crate::{
+    aff::{Affine, PiecewiseAffine, PiecewiseMultiAffine},
+    isl_project, isl_transform,
+    map::{BasicMap, Map},
+    set::{BasicSet, Set},
+    stat::ContextResult,
+    value::Value,
+};
+
+/// Outcome of optimizing an affine objective over a set.
+///
+/// ISL reports an unbounded objective as (negative) infinity and an empty set as NaN;
+/// both are turned into dedicated variants here.
+#[derive(Debug, Clone)]
+pub enum Optimum<'a> {
+    Bounded(Value<'a>),
+    Unbounded,
+    Empty,
+}
+
+impl<'a> Optimum<'a> {
+    fn from_value(value: Value<'a>) -> Result<Self, crate::Error> {
+        let ctx = value.context_ref();
+        if value.is_nan().context_result(ctx)? {
+            Ok(Optimum::Empty)
+        } else if value.is_infty().context_result(ctx)?
+            || value.is_neg_infty().context_result(ctx)?
+        {
+            Ok(Optimum::Unbounded)
+        } else {
+            Ok(Optimum::Bounded(value))
+        }
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        matches!(self, Optimum::Bounded(_))
+    }
+
+    pub fn value(self) -> Option<Value<'a>> {
+        match self {
+            Optimum::Bounded(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Result of a partial lexicographic optimization.
+///
+/// `optimum` is defined on the part of the domain where the problem is feasible,
+/// `empty` is the part of the domain where it is not.
+#[derive(Debug, Clone)]
+pub struct PartialLexOpt<'a, T> {
+    pub optimum: T,
+    pub empty: Set<'a>,
+}
+
+macro_rules! isl_partial_lexopt {
+    ([into($target:ident)] $func:ident, $sys_fn:ident, $dom:ident) => {
+        pub fn $func(
+            self,
+            domain: $crate::isl_macro_impl!(@arg_ty [managed] $dom<'a>),
+        ) -> Result<PartialLexOpt<'a, $target<'a>>, crate::Error> {
+            let ctx = self.context_ref();
+            let this = ManuallyDrop::new(self);
+            let domain = $crate::isl_macro_impl!(@take [managed] domain);
+            let mut empty = std::ptr::null_mut();
+            let raw = unsafe {
+                barvinok_sys::$sys_fn(this.handle.as_ptr(), domain.handle.as_ptr(), &mut empty)
+            };
+            // take ownership of both outputs before reporting any error
+            let empty = NonNull::new(empty).map(|handle| Set {
+                handle,
+                marker: std::marker::PhantomData,
+            });
+            let optimum = NonNull::new(raw)
+                .ok_or_else(|| ctx.last_error_or_unknown())
+                .map(|handle| $target {
+                    handle,
+                    marker: std::marker::PhantomData,
+                })?;
+            let empty = empty.ok_or_else(|| ctx.last_error_or_unknown())?;
+            Ok(PartialLexOpt { optimum, empty })
+        }
+    };
+}
+
+impl<'a> Set<'a> {
+    isl_project!([into(Value)] max_val, isl_set_max_val, [ref] obj: &Affine<'a>);
+    isl_project!([into(Value)] min_val, isl_set_min_val, [ref] obj: &Affine<'a>);
+    isl_transform!([into(Value)] dim_max_val, isl_set_dim_max_val, [cast(i32)] pos: u32);
+    isl_transform!([into(Value)] dim_min_val, isl_set_dim_min_val, [cast(i32)] pos: u32);
+    isl_transform!([into(PiecewiseAffine)] dim_max, isl_set_dim_max, [cast(i32)] pos: u32);
+    isl_transform!([into(PiecewiseAffine)] dim_min, isl_set_dim_min, [cast(i32)] pos: u32);
+    isl_transform!([into(PiecewiseMultiAffine)] lexmin_pw_multi_aff, isl_set_lexmin_pw_multi_aff);
+    isl_transform!([into(PiecewiseMultiAffine)] lexmax_pw_multi_aff, isl_set_lexmax_pw_multi_aff);
+    isl_partial_lexopt!([into(Set)] partial_lexmin, isl_set_partial_lexmin, Set);
+    isl_partial_lexopt!([into(Set)] partial_lexmax, isl_set_partial_lexmax, Set);
+
+    pub fn maximize(&self, obj: &Affine<'a>) -> Result<Optimum<'a>, crate::Error> {
+        Optimum::from_value(self.max_val(obj)?)
+    }
+
+    pub fn minimize(&self, obj: &Affine<'a>) -> Result<Optimum<'a>, crate::Error> {
+        Optimum::from_value(self.min_val(obj)?)
+    }
+}
+
+impl<'a> BasicSet<'a> {
+    isl_project!([into(Value)] max_val, isl_basic_set_max_val, [ref] obj: &Affine<'a>);
+    isl_transform!([into(Value)] dim_max_val, isl_basic_set_dim_max_val, [cast(i32)] pos: u32);
+    isl_partial_lexopt!([into(Set)] partial_lexmin, isl_basic_set_partial_lexmin, BasicSet);
+    isl_partial_lexopt!([into(Set)] partial_lexmax, isl_basic_set_partial_lexmax, BasicSet);
+    isl_partial_lexopt!([into(PiecewiseMultiAffine)] partial_lexmin_pw_multi_aff, isl_basic_set_partial_lexmin_pw_multi_aff, BasicSet);
+    isl_partial_lexopt!([into(PiecewiseMultiAffine)] partial_lexmax_pw_multi_aff, isl_basic_set_partial_lexmax_pw_multi_aff, BasicSet);
+
+    /// ISL only provides the maximum for basic sets, so the minimum goes through [`Set`].
+    pub fn min_val(&self, obj: &Affine<'a>) -> Result<Value<'a>, crate::Error> {
+        Set::try_from(self.clone())?.min_val(obj)
+    }
+
+    pub fn maximize(&self, obj: &Affine<'a>) -> Result<Optimum<'a>, crate::Error> {
+        Optimum::from_value(self.max_val(obj)?)
+    }
+
+    pub fn minimize(&self, obj: &Affine<'a>) -> Result<Optimum<'a>, crate::Error> {
+        Optimum::from_value(self.min_val(obj)?)
+    }
+}
+
+impl<'a> Map<'a> {
+    isl_transform!([into(PiecewiseAffine)] dim_max, isl_map_dim_max, [cast(i32)] pos: u32);
+    isl_transform!([into(PiecewiseAffine)] dim_min, isl_map_dim_min, [cast(i32)] pos: u32);
+    isl_transform!([into(PiecewiseMultiAffine)] lexmin_pw_multi_aff, isl_map_lexmin_pw_multi_aff);
+    isl_transform!([into(PiecewiseMultiAffine)] lexmax_pw_multi_aff, isl_map_lexmax_pw_multi_aff);
+    isl_partial_lexopt!([into(Map)] partial_lexmin, isl_map_partial_lexmin, Set);
+    isl_partial_lexopt!([into(Map)] partial_lexmax, isl_map_partial_lexmax, Set);
+}
+
+impl<'a> BasicMap<'a> {
+    isl_transform!([into(PiecewiseMultiAffine)] lexmin_pw_multi_aff, isl_basic_map_lexmin_pw_multi_aff);
+    isl_partial_lexopt!([into(Map)] partial_lexmin, isl_basic_map_partial_lexmin, BasicSet);
+    isl_partial_lexopt!([into(Map)] partial_lexmax, isl_basic_map_partial_lexmax, BasicSet);
+    isl_partial_lexopt!([into(PiecewiseMultiAffine)] partial_lexmin_pw_multi_aff, isl_basic_map_partial_lexmin_pw_multi_aff, BasicSet);
+    isl_partial_lexopt!([into(PiecewiseMultiAffine)] partial_lexmax_pw_multi_aff, isl_basic_map_partial_lexmax_pw_multi_aff, BasicSet);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+
+    #[test]
+    fn test_max_val() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "{ [i, j] : 0 <= i < 10 and 0 <= j <= i }")?;
+            let obj = Affine::from_str(ctx, "{ [i, j] -> [(i + 2j)] }")?;
+            let max = set.maximize(&obj)?.value().unwrap();
+            assert_eq!(max.numerator(), 27);
+            let min = set.minimize(&obj)?.value().unwrap();
+            assert_eq!(min.numerator(), 0);
+
+            let unbounded = Set::from_str(ctx, "{ [i, j] : i >= 0 and j >= 0 }")?;
+            assert!(matches!(unbounded.maximize(&obj)?, Optimum::Unbounded));
+            let empty = Set::from_str(ctx, "{ [i, j] : i > j > i }")?;
+            assert!(matches!(empty.maximize(&obj)?, Optimum::Empty));
+
+            let bset = BasicSet::from_str(ctx, "{ [i, j] : 0 <= i < 10 and 0 <= j <= i }")?;
+            assert_eq!(bset.max_val(&obj)?.numerator(), 27);
+            assert_eq!(bset.min_val(&obj)?.numerator(), 0);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_dim_max() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "[n, m] -> { [i] : 0 <= i < n and i <= m }")?;
+            let max = set.clone().dim_max(0)?;
+            let expected = PiecewiseAffine::from_str(
+                ctx,
+                "[n, m] -> { [(n - 1)] : 0 < n <= m + 1; [(m)] : m >= 0 and n > m + 1 }",
+            )?;
+            assert_eq!(max, expected);
+            assert_eq!(set.dim_min_val(0)?.numerator(), 0);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_partial_lexmin() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let map = Map::from_str(ctx, "[n] -> { [i] -> [j] : i <= j < n }")?;
+            let domain = Set::from_str(ctx, "[n] -> { [i] : 0 <= i < 10 }")?;
+            let PartialLexOpt { optimum, empty } = map.clone().partial_lexmin(&domain)?;
+            let expected = Map::from_str(ctx, "[n] -> { [i] -> [i] : 0 <= i < 10 and i < n }")?;
+            assert!(optimum.equal(&expected)?);
+            let expected_empty = Set::from_str(ctx, "[n] -> { [i] : 0 <= i < 10 and i >= n }")?;
+            assert!(empty.checked_eq(&expected_empty)?);
+            let lexmax = map.clone().partial_lexmax(domain)?;
+            assert!(lexmax.empty.checked_eq(&expected_empty)?);
+
+            let pma = map.lexmin_pw_multi_aff()?;
+            assert!(
+                pma.to_map()?
+                    .equal(&Map::from_str(ctx, "[n] -> { [i] -> [i] : i < n }")?)?
+            );
+            Ok(())
+        })
+    }
+}
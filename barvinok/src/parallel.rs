@@ -0,0 +1,417 @@
+//! Running independent ISL computations on a pool of threads.
+//!
+//! ISL objects cannot leave the thread of their [`Context`], so every worker of a
+//! [`ContextPool`] owns its own context. Tasks receive that context together with a plain
+//! `Send` input, such as the textual form of a set, and must return plain `Send` data,
+//! such as a string produced by [`IslPrintable::to_isl_string`](crate::traits::IslPrintable::to_isl_string).
+//!
+//! ```ignore
+//! let pool = ContextPool::new(4);
+//! let counts = pool.map(inputs, |ctx, input: String| {
+//!     let set = Set::from_str(ctx, &input)?;
+//!     Ok(set.cardinality()?.to_isl_string())
+//! });
+//! ```
+
+use std::{
+    cell::Cell,
+    panic::{AssertUnwindSafe, catch_unwind, resume_unwind},
+    ptr::NonNull,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread::JoinHandle,
+};
+
+use crate::{Context, ContextRef, Error};
+
+type Job = Box<dyn for<'a> FnOnce(ContextRef<'a>) + Send>;
+
+thread_local! {
+    /// Id of the pool the current thread is a worker of, zero outside of any pool.
+    static WORKER_OF: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A fixed set of worker threads, each owning a [`Context`].
+pub struct ContextPool {
+    jobs: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    /// Address of the shared job queue, which identifies the pool.
+    id: usize,
+}
+
+impl ContextPool {
+    /// Start a pool with `threads` workers, at least one.
+    pub fn new(threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let id = Arc::as_ptr(&receiver) as usize;
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let receiver = receiver.clone();
+                std::thread::spawn(move || worker(receiver, id))
+            })
+            .collect();
+        Self {
+            jobs: Some(sender),
+            workers,
+            id,
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Run `task` on every input and return the results in input order.
+    ///
+    /// A panic in a task is resumed on the calling thread. A task must not call back into
+    /// the pool running it, since it would wait on workers that may all be waiting too;
+    /// such a call fails every input with [`Error::ReentrantPool`] instead.
+    pub fn map<I, R, F>(&self, inputs: Vec<I>, task: F) -> Vec<Result<R, Error>>
+    where
+        I: Send + 'static,
+        R: Send + 'static,
+        F: for<'a> Fn(ContextRef<'a>, I) -> Result<R, Error> + Send + Sync + 'static,
+    {
+        self.map_with(inputs, &TaskOptions::default(), task)
+    }
+
+    /// Like [`ContextPool::map`], with an operation quota and cancellation applied to every task.
+    pub fn map_with<I, R, F>(
+        &self,
+        inputs: Vec<I>,
+        options: &TaskOptions,
+        task: F,
+    ) -> Vec<Result<R, Error>>
+    where
+        I: Send + 'static,
+        R: Send + 'static,
+        F: for<'a> Fn(ContextRef<'a>, I) -> Result<R, Error> + Send + Sync + 'static,
+    {
+        if WORKER_OF.get() == self.id {
+            return inputs.iter().map(|_| Err(Error::ReentrantPool)).collect();
+        }
+        let task = Arc::new(task);
+        let (sender, receiver) = mpsc::channel();
+        let count = inputs.len();
+        for (index, input) in inputs.into_iter().enumerate() {
+            let task = task.clone();
+            let options = options.clone();
+            let sender = sender.clone();
+            self.submit(Box::new(move |ctx| {
+                let result = catch_unwind(AssertUnwindSafe(|| {
+                    run_task(ctx, &options, || task(ctx, input))
+                }));
+                // the caller only stops listening if another task panicked
+                let _ = sender.send((index, result));
+            }));
+        }
+        drop(sender);
+
+        let mut results: Vec<Option<Result<R, Error>>> = (0..count).map(|_| None).collect();
+        for (index, result) in receiver {
+            match result {
+                Ok(result) => results[index] = Some(result),
+                Err(panic) => resume_unwind(panic),
+            }
+        }
+        results
+            .into_iter()
+            .map(|result| result.expect("worker thread exited before finishing its task"))
+            .collect()
+    }
+
+    fn submit(&self, job: Job) {
+        self.jobs
+            .as_ref()
+            .expect("pool is running")
+            .send(job)
+            .expect("worker threads exited");
+    }
+}
+
+impl Drop for ContextPool {
+    fn drop(&mut self) {
+        // closing the channel stops the workers once the queue is drained
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn worker(jobs: Arc<Mutex<Receiver<Job>>>, pool: usize) {
+    WORKER_OF.set(pool);
+    let context = Context::new();
+    context.scope(|ctx| {
+        loop {
+            let job = jobs
+                .lock()
+                .map_err(|_| ())
+                .and_then(|jobs| jobs.recv().map_err(|_| ()));
+            match job {
+                Ok(job) => job(ctx),
+                Err(()) => break,
+            }
+        }
+    })
+}
+
+fn run_task<R>(
+    ctx: ContextRef<'_>,
+    options: &TaskOptions,
+    task: impl FnOnce() -> Result<R, Error>,
+) -> Result<R, Error> {
+    let running = match &options.cancel {
+        Some(token) => Some(token.enter(ctx).ok_or(Error::Cancelled)?),
+        None => None,
+    };
+    let cleanup = Cleanup { ctx, running };
+    ctx.reset_operations();
+    ctx.set_max_operations(options.max_operations.unwrap_or(0));
+    let result = task();
+    drop(cleanup);
+    match &options.cancel {
+        Some(token) if result.is_err() && token.is_cancelled() => Err(Error::Cancelled),
+        _ => result,
+    }
+}
+
+/// Restores the worker context after a task, also when the task panics.
+struct Cleanup<'a, 't> {
+    ctx: ContextRef<'a>,
+    running: Option<Running<'t>>,
+}
+
+impl Drop for Cleanup<'_, '_> {
+    fn drop(&mut self) {
+        // no abort can reach the context once it is unregistered
+        self.running.take();
+        self.ctx.set_max_operations(0);
+        unsafe {
+            barvinok_sys::isl_ctx_resume(self.ctx.0.as_ptr());
+            barvinok_sys::isl_ctx_reset_error(self.ctx.0.as_ptr());
+        }
+    }
+}
+
+/// Limits applied to each task of a [`ContextPool::map_with`] call.
+#[derive(Debug, Clone, Default)]
+pub struct TaskOptions {
+    max_operations: Option<usize>,
+    cancel: Option<CancelToken>,
+}
+
+impl TaskOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail a task with an ISL quota error after `max_operations` ISL operations.
+    pub fn max_operations(mut self, max_operations: usize) -> Self {
+        self.max_operations = Some(max_operations);
+        self
+    }
+
+    /// Stop the tasks when `token` is cancelled.
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+}
+
+/// Cancels the tasks it was given to.
+///
+/// Tasks that have not started fail with [`Error::Cancelled`]. Running tasks are aborted at
+/// their next ISL operation and also fail with [`Error::Cancelled`].
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<CancelState>);
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    running: Mutex<Vec<RawContext>>,
+}
+
+#[derive(Debug)]
+struct RawContext(NonNull<barvinok_sys::isl_ctx>);
+
+// Only used to call `isl_ctx_abort`, which ISL allows from any thread.
+unsafe impl Send for RawContext {}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        let running = self.running();
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        for ctx in running.iter() {
+            unsafe { barvinok_sys::isl_ctx_abort(ctx.0.as_ptr()) };
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn running(&self) -> std::sync::MutexGuard<'_, Vec<RawContext>> {
+        self.0
+            .running
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Register a context running a task, unless the token is already cancelled.
+    fn enter(&self, ctx: ContextRef<'_>) -> Option<Running<'_>> {
+        let mut running = self.running();
+        if self.is_cancelled() {
+            return None;
+        }
+        running.push(RawContext(ctx.0));
+        Some(Running {
+            token: self,
+            ctx: ctx.0,
+        })
+    }
+}
+
+struct Running<'t> {
+    token: &'t CancelToken,
+    ctx: NonNull<barvinok_sys::isl_ctx>,
+}
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        let mut running = self.token.running();
+        if let Some(pos) = running.iter().position(|ctx| ctx.0 == self.ctx) {
+            running.swap_remove(pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set::Set, traits::IslPrintable};
+
+    #[test]
+    fn test_map_keeps_input_order() {
+        let pool = ContextPool::new(3);
+        let inputs = (0..20)
+            .map(|n| format!("{{ [i] : 0 <= i < {n} }}"))
+            .collect();
+        let results = pool.map(inputs, |ctx, input: String| {
+            let set = Set::from_str(ctx, &input)?;
+            if set.is_empty()? {
+                return Ok(None);
+            }
+            Ok(Some(set.lexmax()?.to_isl_string()))
+        });
+        assert_eq!(results.len(), 20);
+        assert!(matches!(results[0], Ok(None)));
+        for (n, result) in results.iter().enumerate().skip(1) {
+            let expected = format!("{{ [i = {}] }}", n - 1);
+            assert_eq!(result.as_ref().unwrap().as_deref(), Some(expected.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_operation_quota() {
+        let pool = ContextPool::new(1);
+        let options = TaskOptions::new().max_operations(1);
+        let inputs = vec!["{ [i, j] : 0 <= i < j < 10 }".to_string()];
+        let results = pool.map_with(inputs.clone(), &options, |ctx, input: String| {
+            Set::from_str(ctx, &input).map(|set| set.to_isl_string())
+        });
+        assert!(matches!(results[0], Err(Error::IslError(_))));
+        // the quota does not outlive the task
+        let results = pool.map(inputs, |ctx, input: String| {
+            Set::from_str(ctx, &input).map(|set| set.to_isl_string())
+        });
+        assert!(results[0].is_ok());
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let pool = ContextPool::new(1);
+        let token = CancelToken::new();
+        let options = TaskOptions::new().cancel_token(token.clone());
+        let results = pool.map_with((0..4).collect(), &options, move |ctx, n: u32| {
+            if n == 1 {
+                token.cancel();
+                // aborted at the next ISL operation
+                Set::from_str(ctx, "{ [i] : 0 <= i < 10 }")?;
+            }
+            Ok(n)
+        });
+        assert!(matches!(results[0], Ok(0)));
+        assert!(
+            results[1..]
+                .iter()
+                .all(|r| matches!(r, Err(Error::Cancelled)))
+        );
+
+        // the worker context is usable again
+        let results = pool.map(vec![()], |ctx, ()| {
+            Set::from_str(ctx, "{ [i] : 0 <= i < 10 }").map(|_| ())
+        });
+        assert!(results[0].is_ok());
+    }
+
+    #[test]
+    fn test_panic_after_cancellation() {
+        let pool = ContextPool::new(1);
+        let token = CancelToken::new();
+        let options = TaskOptions::new().cancel_token(token.clone());
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            pool.map_with(vec![()], &options, move |ctx, ()| {
+                token.cancel();
+                let aborted = Set::from_str(ctx, "{ [i] : 0 <= i < 10 }");
+                assert!(aborted.is_ok(), "task failed");
+                Ok(())
+            })
+        }));
+        assert!(result.is_err());
+
+        // the panic did not leave the worker context aborted
+        let results = pool.map(vec![()], |ctx, ()| {
+            Set::from_str(ctx, "{ [i] : 0 <= i < 10 }").map(|_| ())
+        });
+        assert!(results[0].is_ok());
+    }
+
+    #[test]
+    fn test_reentrant_map_fails() {
+        let pool = Arc::new(ContextPool::new(1));
+        let inner = pool.clone();
+        let results = pool.map(vec![()], move |_, ()| {
+            let nested = inner.map(vec![1, 2], |_, n: i32| Ok(n));
+            Ok(nested
+                .iter()
+                .all(|r| matches!(r, Err(Error::ReentrantPool))))
+        });
+        assert!(matches!(results[0], Ok(true)));
+
+        // other pools can still be used from a task
+        let results = pool.map(vec![()], |_, ()| {
+            let other = ContextPool::new(1);
+            other.map(vec![3], |_, n: i32| Ok(n)).remove(0)
+        });
+        assert!(matches!(results[0], Ok(3)));
+    }
+
+    #[test]
+    #[should_panic(expected = "task failed")]
+    fn test_panics_propagate() {
+        let pool = ContextPool::new(2);
+        pool.map(vec![1, 2], |_, n: i32| {
+            assert!(n != 2, "task failed");
+            Ok(n)
+        });
+    }
+}
@@ -0,0 +1,204 @@
+//! Structural comparison of ISL objects, for using them as map keys.
+//!
+//! The `PartialEq` implementations of sets and maps compare the represented sets. That is
+//! exact but expensive, and no hash can agree with it. [`PlainEq`] compares the internal
+//! representation instead: equal keys represent the same object, but one set can be
+//! written in several ways that compare unequal, so a lookup may miss an equal set.
+
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    aff::{Affine, PiecewiseAffine},
+    map::Map,
+    set::Set,
+};
+
+/// Objects with a total order on their internal representation and a matching hash.
+pub trait PlainCmp {
+    /// Hash of the internal representation. Objects comparing equal have the same hash.
+    fn plain_hash(&self) -> u32;
+
+    fn plain_cmp(&self, other: &Self) -> Ordering;
+}
+
+impl<T: PlainCmp + ?Sized> PlainCmp for &T {
+    fn plain_hash(&self) -> u32 {
+        (**self).plain_hash()
+    }
+
+    fn plain_cmp(&self, other: &Self) -> Ordering {
+        (**self).plain_cmp(*other)
+    }
+}
+
+/// Compares, orders and hashes the wrapped object by its internal representation,
+/// see the [module documentation](self).
+///
+/// ```ignore
+/// let mut cache = HashMap::new();
+/// cache.insert(PlainEq(set.clone()), set.cardinality()?);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PlainEq<T>(pub T);
+
+impl<T> PlainEq<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: PlainCmp> PartialEq for PlainEq<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.plain_cmp(&other.0).is_eq()
+    }
+}
+
+impl<T: PlainCmp> Eq for PlainEq<T> {}
+
+impl<T: PlainCmp> PartialOrd for PlainEq<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: PlainCmp> Ord for PlainEq<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.plain_cmp(&other.0)
+    }
+}
+
+impl<T: PlainCmp> Hash for PlainEq<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.plain_hash().hash(state);
+    }
+}
+
+impl<T> std::ops::Deref for PlainEq<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl PlainCmp for Set<'_> {
+    fn plain_hash(&self) -> u32 {
+        unsafe { barvinok_sys::isl_set_get_hash(self.handle.as_ptr()) }
+    }
+
+    fn plain_cmp(&self, other: &Self) -> Ordering {
+        self.plain_compare(other)
+    }
+}
+
+impl PlainCmp for PiecewiseAffine<'_> {
+    fn plain_hash(&self) -> u32 {
+        unsafe { barvinok_sys::isl_pw_aff_get_hash(self.handle.as_ptr()) }
+    }
+
+    fn plain_cmp(&self, other: &Self) -> Ordering {
+        let cmp = unsafe {
+            barvinok_sys::isl_pw_aff_plain_cmp(self.handle.as_ptr(), other.handle.as_ptr())
+        };
+        cmp.cmp(&0)
+    }
+}
+
+/// ISL has no `isl_map_plain_cmp`. An `isl_set` is an `isl_map` with a set space inside
+/// ISL, and `isl_set_plain_cmp` compares the full space of each basic map, so it is used
+/// on the map handles directly.
+impl PlainCmp for Map<'_> {
+    fn plain_hash(&self) -> u32 {
+        unsafe { barvinok_sys::isl_map_get_hash(self.handle.as_ptr()) }
+    }
+
+    fn plain_cmp(&self, other: &Self) -> Ordering {
+        let cmp = unsafe {
+            barvinok_sys::isl_set_plain_cmp(
+                self.handle.as_ptr().cast(),
+                other.handle.as_ptr().cast(),
+            )
+        };
+        cmp.cmp(&0)
+    }
+}
+
+// `isl_aff_plain_cmp` is exported by ISL (it backs `isl_pw_aff_plain_cmp`) but only
+// declared in a private header, so it is not part of the generated bindings.
+unsafe extern "C" {
+    fn isl_aff_plain_cmp(
+        aff1: *mut barvinok_sys::isl_aff,
+        aff2: *mut barvinok_sys::isl_aff,
+    ) -> std::ffi::c_int;
+}
+
+impl PlainCmp for Affine<'_> {
+    fn plain_hash(&self) -> u32 {
+        unsafe { barvinok_sys::isl_aff_get_hash(self.handle.as_ptr()) }
+    }
+
+    fn plain_cmp(&self, other: &Self) -> Ordering {
+        let cmp = unsafe { isl_aff_plain_cmp(self.handle.as_ptr(), other.handle.as_ptr()) };
+        cmp.cmp(&0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashMap};
+
+    use super::*;
+    use crate::Context;
+
+    #[test]
+    fn test_sets_as_keys() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let a = Set::from_str(ctx, "[n] -> { [i] : 0 <= i < n }")?;
+            let b = Set::from_str(ctx, "[n] -> { [i] : 0 <= i <= n }")?;
+            let mut cache = HashMap::new();
+            cache.insert(PlainEq(a.clone()), 1);
+            cache.insert(PlainEq(b.clone()), 2);
+            cache.insert(
+                PlainEq(Set::from_str(ctx, "[n] -> { [i] : 0 <= i < n }")?),
+                3,
+            );
+            assert_eq!(cache.len(), 2);
+            assert_eq!(cache[&PlainEq(a.clone())], 3);
+            assert_eq!(cache[&PlainEq(b.clone())], 2);
+
+            let ordered: BTreeSet<_> = [&b, &a, &b].into_iter().map(PlainEq).collect();
+            assert_eq!(ordered.len(), 2);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_maps_and_affine_expressions() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let m1 = Map::from_str(ctx, "{ A[i] -> B[i + 1] }")?;
+            let m2 = Map::from_str(ctx, "{ A[i] -> C[i + 1] }")?;
+            assert_eq!(PlainEq(&m1), PlainEq(&m1.clone()));
+            assert_ne!(PlainEq(&m1), PlainEq(&m2));
+            let m3 = Map::from_str(ctx, "{ [i, j] -> [] : i = j + 1 }")?;
+            let m4 = Map::from_str(ctx, "{ [i] -> [j] : i = j + 1 }")?;
+            assert_ne!(PlainEq(&m3), PlainEq(&m4));
+            assert_eq!(
+                PlainEq(&m1).cmp(&PlainEq(&m2)),
+                PlainEq(&m2).cmp(&PlainEq(&m1)).reverse()
+            );
+
+            let a1 = Affine::from_str(ctx, "{ [i] -> [(2i + 1)] }")?;
+            let a2 = Affine::from_str(ctx, "{ [i] -> [(2i + 1)] }")?;
+            let a3 = Affine::from_str(ctx, "{ [i] -> [(i)] }")?;
+            assert_eq!(PlainEq(&a1), PlainEq(&a2));
+            assert_eq!(a1.plain_hash(), a2.plain_hash());
+            assert_ne!(PlainEq(&a1), PlainEq(&a3));
+            Ok(())
+        })
+    }
+}
@@ -2,14 +2,17 @@ use crate::isl_ctor;
 use crate::point::Point;
 use crate::{DimType, space::Space};
 use crate::{
+    aff::Affine,
+    callback::{Callback, IterationControl},
     ident::Ident,
     impl_isl_handle, isl_flag, isl_project, isl_size, isl_transform,
     set::Set,
     stat::{isl_bool_to_optional_bool, isl_size_to_optional_u32},
+    traits::{HasSpace, IslPrintable},
     value::Value,
 };
 use std::mem::ManuallyDrop;
-use std::{cell::Cell, ptr::NonNull};
+use std::ptr::NonNull;
 
 impl_isl_handle!([printer] QuasiPolynomial, qpolynomial);
 impl_isl_handle!([noprint] Term, term);
@@ -31,7 +34,7 @@ impl<'a> QuasiPolynomial<'a> {
     isl_project!([into(Space)] get_space, isl_qpolynomial_get_space);
     isl_size!(qpolynomial_dim => get_dim, [cast(u32)] dim_type: DimType);
     isl_ctor!(val_on_domain, isl_qpolynomial_val_on_domain, space: Space<'a>, [managed] value: Value<'a>);
-    isl_ctor!(var_on_domain, isl_qpolynomial_var_on_domain, space: Space<'a>, [cast(u32)] dim_type: DimType, [trivial] pos: u32);
+    isl_ctor!(var_on_domain, isl_qpolynomial_var_on_domain, space: Space<'a>, dim: Dim);
     isl_project!([into(Value)] get_constant_val, isl_qpolynomial_get_constant_val);
     isl_flag!(qpolynomial_is_zero => is_zero);
     isl_flag!(qpolynomial_is_infty => is_infty);
@@ -50,79 +53,46 @@ impl<'a> QuasiPolynomial<'a> {
     isl_transform!(gist_params, isl_qpolynomial_gist_params, [managed] set: Set<'a>);
     isl_flag!(qpolynomial_involves_dims => involves_dims, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [trivial] num: u32);
     isl_transform!([into(Value)] eval, isl_qpolynomial_eval, [managed] point: Point<'a>);
-    pub fn foreach_term<F>(&self, func: F) -> Result<(), crate::Error>
+    pub fn foreach_term<F, R>(&self, func: F) -> Result<(), crate::Error>
     where
-        F: FnMut(Term<'a>) -> Result<(), crate::Error>,
+        F: FnMut(Term<'a>) -> Result<R, crate::Error>,
+        R: IterationControl,
     {
-        struct FuncWithState<F> {
-            func: F,
-            state: Cell<Result<(), crate::Error>>,
-        }
-        let mut func = FuncWithState {
-            func,
-            state: Cell::new(Ok(())),
-        };
-        unsafe extern "C" fn callback<'a, F>(
+        unsafe extern "C" fn callback<'a, F, R>(
             term: *mut barvinok_sys::isl_term,
             user: *mut std::ffi::c_void,
         ) -> barvinok_sys::isl_stat
         where
-            F: FnMut(Term<'a>) -> Result<(), crate::Error>,
+            F: FnMut(Term<'a>) -> Result<R, crate::Error>,
+            R: IterationControl,
         {
-            let data = unsafe { &mut *(user as *mut FuncWithState<F>) };
+            let Some(term) = NonNull::new(term) else {
+                return barvinok_sys::isl_stat_isl_stat_error;
+            };
+            let state = unsafe { Callback::<F>::from_user(user) };
             let term = Term {
-                handle: NonNull::new(term).unwrap(),
+                handle: term,
                 marker: std::marker::PhantomData,
             };
-            let state = data.state.replace(Ok(()));
-            data.state.set(state.and_then(|_| (data.func)(term)));
-            if data.state.get_mut().is_ok() {
-                barvinok_sys::isl_stat_isl_stat_ok
-            } else {
-                barvinok_sys::isl_stat_isl_stat_error
-            }
+            state.run_stat(|func| func(term))
         }
-        let handle = self.handle.as_ptr();
+        let mut state = Callback::new(func);
         let res = unsafe {
             barvinok_sys::isl_qpolynomial_foreach_term(
-                handle,
-                Some(callback::<F>),
-                &mut func as *mut FuncWithState<F> as *mut std::ffi::c_void,
+                self.handle.as_ptr(),
+                Some(callback::<F, R>),
+                state.as_user(),
             )
         };
-        if res == barvinok_sys::isl_stat_isl_stat_ok {
-            func.state.into_inner()
-        } else {
-            match func.state.into_inner() {
-                Ok(()) => Err(self.context_ref().last_error_or_unknown().into()),
-                Err(e) => Err(e),
-            }
-        }
-    }
-}
-
-impl<'a> std::ops::Add for QuasiPolynomial<'a> {
-    type Output = QuasiPolynomial<'a>;
-    fn add(self, other: QuasiPolynomial<'a>) -> Self::Output {
-        self.checked_add(other).unwrap()
-    }
-}
-
-impl<'a> std::ops::Sub for QuasiPolynomial<'a> {
-    type Output = QuasiPolynomial<'a>;
-    fn sub(self, other: QuasiPolynomial<'a>) -> Self::Output {
-        self.checked_sub(other).unwrap()
-    }
-}
-
-impl<'a> std::ops::Mul for QuasiPolynomial<'a> {
-    type Output = QuasiPolynomial<'a>;
-    fn mul(self, other: QuasiPolynomial<'a>) -> Self::Output {
-        self.checked_mul(other).unwrap()
+        state.finish(
+            res != barvinok_sys::isl_stat_isl_stat_ok,
+            self.context_ref(),
+        )
     }
 }
 
 impl<'a> PiecewiseQuasiPolynomial<'a> {
+    isl_ctor!([ctx] from_str, isl_pw_qpolynomial_read_from_str, [str] str: &str);
     isl_project!([into(Space)] get_space, isl_pw_qpolynomial_get_space);
     isl_flag!(pw_qpolynomial_involves_nan => involves_nan);
     isl_flag!(pw_qpolynomial_plain_is_equal => plain_is_equal, [ref] other: &PiecewiseQuasiPolynomial<'a>);
@@ -134,7 +104,7 @@ impl<'a> PiecewiseQuasiPolynomial<'a> {
     isl_flag!(pw_qpolynomial_involves_param_id => involves_param_id, [ref] id: Ident<'a>);
     isl_flag!(pw_qpolynomial_involves_dims => involves_dims, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [trivial] num: u32);
     isl_flag!(pw_qpolynomial_has_equal_space => has_equal_space, [ref] other: &PiecewiseQuasiPolynomial<'a>);
-    isl_transform!(set_dim_name, isl_pw_qpolynomial_set_dim_name, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [str] name: &str);
+    isl_transform!(set_dim_name, isl_pw_qpolynomial_set_dim_name, dim: Dim, [str] name: &str);
     isl_size!(pw_qpolynomial_find_dim_by_name => find_dim_by_name, [cast(u32)] dim_type: DimType, [str] name: &str);
     isl_transform!(reset_user, isl_pw_qpolynomial_reset_user);
     isl_transform!([into(Set)] domain, isl_pw_qpolynomial_domain);
@@ -163,79 +133,59 @@ impl<'a> PiecewiseQuasiPolynomial<'a> {
     isl_transform!(insert_dims, isl_pw_qpolynomial_insert_dims, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [trivial] num: u32);
     isl_transform!(add_dims, isl_pw_qpolynomial_add_dims, [cast(u32)] dim_type: DimType, [trivial] num: u32);
     isl_transform!(move_dims, isl_pw_qpolynomial_move_dims, [cast(u32)] dst_dim_type : DimType, [trivial] dst_pos: u32, [cast(u32)] src_dim_type: DimType, [trivial] src_pos: u32, [trivial] num: u32);
-    isl_transform!(fix_val, isl_pw_qpolynomial_fix_val, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [managed] value: Value<'a>);
+    isl_transform!(fix_val, isl_pw_qpolynomial_fix_val, dim: Dim, [managed] value: Value<'a>);
     isl_transform!([into(Value)] eval, isl_pw_qpolynomial_eval, [managed] point: Point<'a>);
     isl_transform!([into(Value)] max, isl_pw_qpolynomial_max);
     isl_transform!([into(Value)] min, isl_pw_qpolynomial_min);
     isl_size!(pw_qpolynomial_n_piece => num_pieces);
     isl_flag!(pw_qpolynomial_isa_qpolynomial => is_qpolynomial);
     isl_transform!([into(QuasiPolynomial)] as_qpolynomial, isl_pw_qpolynomial_as_qpolynomial);
-    pub fn foreach_piece<F>(&self, func: F) -> Result<(), crate::Error>
+    pub fn foreach_piece<F, R>(&self, func: F) -> Result<(), crate::Error>
     where
-        F: FnMut(QuasiPolynomial<'a>, Set<'a>) -> Result<(), crate::Error>,
+        F: FnMut(QuasiPolynomial<'a>, Set<'a>) -> Result<R, crate::Error>,
+        R: IterationControl,
     {
-        struct FuncWithState<F> {
-            func: F,
-            state: Cell<Result<(), crate::Error>>,
-        }
-        let mut func = FuncWithState {
-            func,
-            state: Cell::new(Ok(())),
-        };
-        unsafe extern "C" fn callback<'a, F>(
+        unsafe extern "C" fn callback<'a, F, R>(
             set: *mut barvinok_sys::isl_set,
             qpoly: *mut barvinok_sys::isl_qpolynomial,
             user: *mut std::ffi::c_void,
         ) -> barvinok_sys::isl_stat
         where
-            F: FnMut(QuasiPolynomial<'a>, Set<'a>) -> Result<(), crate::Error>,
+            F: FnMut(QuasiPolynomial<'a>, Set<'a>) -> Result<R, crate::Error>,
+            R: IterationControl,
         {
-            let data = unsafe { &mut *(user as *mut FuncWithState<F>) };
-            let qpoly = QuasiPolynomial {
-                handle: NonNull::new(qpoly).unwrap(),
+            let qpoly = NonNull::new(qpoly).map(|handle| QuasiPolynomial {
+                handle,
                 marker: std::marker::PhantomData,
-            };
-            let set = Set {
-                handle: NonNull::new(set).unwrap(),
+            });
+            let set = NonNull::new(set).map(|handle| Set {
+                handle,
                 marker: std::marker::PhantomData,
+            });
+            // the piece that was passed is freed on return
+            let (Some(qpoly), Some(set)) = (qpoly, set) else {
+                return barvinok_sys::isl_stat_isl_stat_error;
             };
-            let state = data.state.replace(Ok(()));
-            data.state.set(state.and_then(|_| (data.func)(qpoly, set)));
-            if data.state.get_mut().is_ok() {
-                barvinok_sys::isl_stat_isl_stat_ok
-            } else {
-                barvinok_sys::isl_stat_isl_stat_error
-            }
+            let state = unsafe { Callback::<F>::from_user(user) };
+            state.run_stat(|func| func(qpoly, set))
         }
-        let handle = self.handle.as_ptr();
+        let mut state = Callback::new(func);
         let res = unsafe {
             barvinok_sys::isl_pw_qpolynomial_foreach_piece(
-                handle,
-                Some(callback::<F>),
-                &mut func as *mut FuncWithState<F> as *mut std::ffi::c_void,
+                self.handle.as_ptr(),
+                Some(callback::<F, R>),
+                state.as_user(),
             )
         };
-        if res == barvinok_sys::isl_stat_isl_stat_ok {
-            func.state.into_inner()
-        } else {
-            match func.state.into_inner() {
-                Ok(()) => Err(self.context_ref().last_error_or_unknown().into()),
-                Err(e) => Err(e),
-            }
-        }
+        state.finish(
+            res != barvinok_sys::isl_stat_isl_stat_ok,
+            self.context_ref(),
+        )
     }
     pub fn every_piece<F>(&self, func: F) -> Result<bool, crate::Error>
     where
         F: FnMut(QuasiPolynomial<'a>, Set<'a>) -> Result<bool, crate::Error>,
     {
-        struct FuncWithState<F> {
-            func: F,
-            state: Cell<Result<(), crate::Error>>,
-        }
-        let mut func = FuncWithState {
-            func,
-            state: Cell::new(Ok(())),
-        };
         unsafe extern "C" fn callback<'a, F>(
             set: *mut barvinok_sys::isl_set,
             qpoly: *mut barvinok_sys::isl_qpolynomial,
@@ -244,135 +194,181 @@ impl<'a> PiecewiseQuasiPolynomial<'a> {
         where
             F: FnMut(QuasiPolynomial<'a>, Set<'a>) -> Result<bool, crate::Error>,
         {
-            let data = unsafe { &mut *(user as *mut FuncWithState<F>) };
-            let qpoly = QuasiPolynomial {
-                handle: NonNull::new(qpoly).unwrap(),
+            let qpoly = NonNull::new(qpoly).map(|handle| QuasiPolynomial {
+                handle,
                 marker: std::marker::PhantomData,
-            };
-            let set = Set {
-                handle: NonNull::new(set).unwrap(),
+            });
+            let set = NonNull::new(set).map(|handle| Set {
+                handle,
                 marker: std::marker::PhantomData,
+            });
+            // the piece that was passed is freed on return
+            let (Some(qpoly), Some(set)) = (qpoly, set) else {
+                return barvinok_sys::isl_bool_isl_bool_error;
             };
-            let state = data.state.replace(Ok(()));
-            match state.and_then(|_| (data.func)(qpoly, set)) {
-                Ok(true) => barvinok_sys::isl_bool_isl_bool_true,
-                Ok(false) => barvinok_sys::isl_bool_isl_bool_false,
-                Err(e) => {
-                    data.state.set(Err(e));
-                    barvinok_sys::isl_bool_isl_bool_error
-                }
-            }
+            let state = unsafe { Callback::<F>::from_user(user) };
+            state.run_bool(|func| func(qpoly, set))
         }
-        let handle = self.handle.as_ptr();
+        let mut state = Callback::new(func);
         let res = unsafe {
             barvinok_sys::isl_pw_qpolynomial_every_piece(
-                handle,
+                self.handle.as_ptr(),
                 Some(callback::<F>),
-                &mut func as *mut FuncWithState<F> as *mut std::ffi::c_void,
+                state.as_user(),
             )
         };
         let res = isl_bool_to_optional_bool(res);
-        match res {
-            Some(true) => Ok(true),
-            Some(false) => Ok(false),
-            None => match func.state.into_inner() {
-                Ok(()) => Err(self.context_ref().last_error_or_unknown().into()),
-                Err(e) => Err(e),
-            },
-        }
+        state.finish(res.is_none(), self.context_ref())?;
+        Ok(res == Some(true))
     }
-    pub fn foreach_lifted_piece<F>(&self, func: F) -> Result<(), crate::Error>
+    pub fn foreach_lifted_piece<F, R>(&self, func: F) -> Result<(), crate::Error>
     where
-        F: FnMut(QuasiPolynomial<'a>, Set<'a>) -> Result<(), crate::Error>,
+        F: FnMut(QuasiPolynomial<'a>, Set<'a>) -> Result<R, crate::Error>,
+        R: IterationControl,
     {
-        struct FuncWithState<F> {
-            func: F,
-            state: Cell<Result<(), crate::Error>>,
-        }
-        let mut func = FuncWithState {
-            func,
-            state: Cell::new(Ok(())),
-        };
-        unsafe extern "C" fn callback<'a, F>(
+        unsafe extern "C" fn callback<'a, F, R>(
             set: *mut barvinok_sys::isl_set,
             qpoly: *mut barvinok_sys::isl_qpolynomial,
             user: *mut std::ffi::c_void,
         ) -> barvinok_sys::isl_stat
         where
-            F: FnMut(QuasiPolynomial<'a>, Set<'a>) -> Result<(), crate::Error>,
+            F: FnMut(QuasiPolynomial<'a>, Set<'a>) -> Result<R, crate::Error>,
+            R: IterationControl,
         {
-            let data = unsafe { &mut *(user as *mut FuncWithState<F>) };
-            let qpoly = QuasiPolynomial {
-                handle: NonNull::new(qpoly).unwrap(),
+            let qpoly = NonNull::new(qpoly).map(|handle| QuasiPolynomial {
+                handle,
                 marker: std::marker::PhantomData,
-            };
-            let set = Set {
-                handle: NonNull::new(set).unwrap(),
+            });
+            let set = NonNull::new(set).map(|handle| Set {
+                handle,
                 marker: std::marker::PhantomData,
+            });
+            // the piece that was passed is freed on return
+            let (Some(qpoly), Some(set)) = (qpoly, set) else {
+                return barvinok_sys::isl_stat_isl_stat_error;
             };
-            let state = data.state.replace(Ok(()));
-            data.state.set(state.and_then(|_| (data.func)(qpoly, set)));
-            if data.state.get_mut().is_ok() {
-                barvinok_sys::isl_stat_isl_stat_ok
-            } else {
-                barvinok_sys::isl_stat_isl_stat_error
-            }
+            let state = unsafe { Callback::<F>::from_user(user) };
+            state.run_stat(|func| func(qpoly, set))
         }
-        let handle = self.handle.as_ptr();
+        let mut state = Callback::new(func);
         let res = unsafe {
             barvinok_sys::isl_pw_qpolynomial_foreach_lifted_piece(
-                handle,
-                Some(callback::<F>),
-                &mut func as *mut FuncWithState<F> as *mut std::ffi::c_void,
+                self.handle.as_ptr(),
+                Some(callback::<F, R>),
+                state.as_user(),
             )
         };
-        if res == barvinok_sys::isl_stat_isl_stat_ok {
-            func.state.into_inner()
-        } else {
-            match func.state.into_inner() {
-                Ok(()) => Err(self.context_ref().last_error_or_unknown().into()),
-                Err(e) => Err(e),
-            }
-        }
+        state.finish(
+            res != barvinok_sys::isl_stat_isl_stat_ok,
+            self.context_ref(),
+        )
     }
     isl_transform!(pw_qpolynomial_coalesce, isl_pw_qpolynomial_coalesce);
+    // Sum over the integer points of the domain. If the domain is a wrapped map, the sum is
+    // taken over its range and the result is defined on its domain.
+    isl_transform!(sum, isl_pw_qpolynomial_sum);
     isl_transform!(gist, isl_pw_qpolynomial_gist, [managed] set: Set<'a>);
     isl_transform!(gist_params, isl_pw_qpolynomial_gist_params, [managed] set: Set<'a>);
     isl_transform!(split_periods, isl_pw_qpolynomial_split_periods, [cast(i32)] num_periods: u32);
 }
 
-impl<'a> std::ops::Add for PiecewiseQuasiPolynomial<'a> {
-    type Output = PiecewiseQuasiPolynomial<'a>;
-    fn add(self, other: PiecewiseQuasiPolynomial<'a>) -> Self::Output {
-        self.checked_add(other).unwrap()
+impl<'a> Term<'a> {
+    isl_size!(term_dim => dim, [cast(u32)] dim_type: DimType);
+    /// Exponent of the variable at `pos` among the dimensions of type `dim_type`.
+    pub fn exponent(&self, dim_type: DimType, pos: u32) -> Result<u32, crate::Error> {
+        // ISL does not expose the space of a term, so only the position can be checked
+        if pos >= self.dim(dim_type)? {
+            return Err(crate::Error::VariablePositionOutOfBounds);
+        }
+        let exponent =
+            unsafe { barvinok_sys::isl_term_get_exp(self.handle.as_ptr(), dim_type as u32, pos) };
+        isl_size_to_optional_u32(exponent)
+            .ok_or_else(|| self.context_ref().last_error_or_unknown().into())
     }
+    isl_project!([into(Value)] coefficient, isl_term_get_coefficient_val);
+    isl_project!([into(Affine)] get_div, isl_term_get_div, [cast(u32)] pos: u32);
 }
 
-impl<'a> std::ops::Sub for PiecewiseQuasiPolynomial<'a> {
-    type Output = PiecewiseQuasiPolynomial<'a>;
-    fn sub(self, other: PiecewiseQuasiPolynomial<'a>) -> Self::Output {
-        self.checked_sub(other).unwrap()
-    }
+/// A [`PiecewiseQuasiPolynomial`] as plain data, for tools that do not link ISL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Formula {
+    /// Parameter names, `None` for anonymous parameters.
+    pub params: Vec<Option<String>>,
+    /// Names of the domain dimensions, `None` for anonymous dimensions.
+    pub variables: Vec<Option<String>>,
+    pub pieces: Vec<FormulaPiece>,
 }
 
-impl<'a> std::ops::Mul for PiecewiseQuasiPolynomial<'a> {
-    type Output = PiecewiseQuasiPolynomial<'a>;
-    fn mul(self, other: PiecewiseQuasiPolynomial<'a>) -> Self::Output {
-        self.checked_mul(other).unwrap()
-    }
+/// A polynomial on a domain, written as a sum of monomials.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FormulaPiece {
+    /// The domain in ISL syntax.
+    pub domain: String,
+    pub terms: Vec<Monomial>,
 }
 
-impl<'a> std::ops::Neg for PiecewiseQuasiPolynomial<'a> {
-    type Output = PiecewiseQuasiPolynomial<'a>;
-    fn neg(self) -> Self::Output {
-        self.checked_neg().unwrap()
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Monomial {
+    /// Exact rational coefficient, such as `-3/2`.
+    pub coefficient: String,
+    /// Exponent of each parameter.
+    pub params: Vec<u32>,
+    /// Exponent of each domain dimension.
+    pub variables: Vec<u32>,
+    /// Integer divisions with a non-zero exponent, as an affine expression in ISL syntax
+    /// and its exponent.
+    pub divs: Vec<(String, u32)>,
+}
+
+impl Monomial {
+    fn from_term(term: &Term<'_>) -> Result<Self, crate::Error> {
+        let exponents = |dim_type| {
+            (0..term.dim(dim_type)?)
+                .map(|pos| term.exponent(dim_type, pos))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let mut divs = Vec::new();
+        for pos in 0..term.dim(DimType::Div)? {
+            let exponent = term.exponent(DimType::Div, pos)?;
+            if exponent != 0 {
+                divs.push((term.get_div(pos)?.to_isl_string(), exponent));
+            }
+        }
+        Ok(Self {
+            coefficient: term.coefficient()?.to_isl_string(),
+            params: exponents(DimType::Param)?,
+            variables: exponents(DimType::Out)?,
+            divs,
+        })
     }
 }
 
-impl<'a> Term<'a> {
-    isl_size!(term_dim => dim, [cast(u32)] dim_type: DimType);
-    isl_size!(term_get_exp => exponent, [cast(u32)] dim_type: DimType, [trivial] pos: u32);
-    isl_project!([into(Value)] coefficient, isl_term_get_coefficient_val);
+impl<'a> PiecewiseQuasiPolynomial<'a> {
+    /// Extract the pieces and their monomials.
+    pub fn to_formula(&self) -> Result<Formula, crate::Error> {
+        let space = self.get_domain_space()?;
+        let mut pieces = Vec::new();
+        self.foreach_piece(|qpoly, domain| {
+            let mut terms = Vec::new();
+            qpoly.foreach_term(|term| {
+                terms.push(Monomial::from_term(&term)?);
+                Ok(())
+            })?;
+            pieces.push(FormulaPiece {
+                domain: domain.to_isl_string(),
+                terms,
+            });
+            Ok(())
+        })?;
+        Ok(Formula {
+            params: space.dim_names(DimType::Param)?,
+            variables: space.dim_names(DimType::Out)?,
+            pieces,
+        })
+    }
 }
 
 impl<'a> TryFrom<QuasiPolynomial<'a>> for PiecewiseQuasiPolynomial<'a> {
@@ -493,7 +489,7 @@ mod tests {
             let space = Space::set(ctx, 1, 2).unwrap();
             let qpoly1 = QuasiPolynomial::one_on_domain(space.clone()).unwrap();
             let qpoly2 = QuasiPolynomial::zero_on_domain(space).unwrap();
-            let qpoly3 = qpoly1 + qpoly2;
+            let qpoly3 = (qpoly1 + qpoly2).unwrap();
             assert_eq!(qpoly3.context_ref().0.as_ptr(), ctx.0.as_ptr());
             println!("{:?}", qpoly3);
         });
@@ -506,7 +502,7 @@ mod tests {
             let space = Space::set(ctx, 1, 2).unwrap();
             let qpoly1 = QuasiPolynomial::zero_on_domain(space.clone()).unwrap();
             let qpoly2 = QuasiPolynomial::one_on_domain(space).unwrap();
-            let qpoly3 = qpoly1 - qpoly2;
+            let qpoly3 = (qpoly1 - qpoly2).unwrap();
             assert_eq!(qpoly3.context_ref().0.as_ptr(), ctx.0.as_ptr());
             println!("{:?}", qpoly3);
             let val = qpoly3.get_constant_val();
@@ -558,4 +554,34 @@ mod tests {
                 .unwrap();
         });
     }
+
+    #[test]
+    fn test_pw_qpolynomial_to_formula() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let pwqp = PiecewiseQuasiPolynomial::from_str(
+                ctx,
+                "[n] -> { [i] -> 2 * floor(i / 2) * n : 0 <= i < n; [i] -> 1 : i >= n }",
+            )?;
+            let formula = pwqp.to_formula()?;
+            assert_eq!(formula.params, [Some("n".to_string())]);
+            assert_eq!(formula.variables, [Some("i".to_string())]);
+            assert_eq!(formula.pieces.len(), 2);
+            let terms = formula
+                .pieces
+                .iter()
+                .flat_map(|piece| &piece.terms)
+                .collect::<Vec<_>>();
+            assert!(terms.iter().any(|term| term.coefficient == "2"
+                && term.params == [1]
+                && term.divs.len() == 1
+                && term.divs[0].1 == 1));
+            assert!(
+                terms.iter().any(|term| term.coefficient == "1"
+                    && term.params == [0]
+                    && term.divs.is_empty())
+            );
+            Ok(())
+        })
+    }
 }
@@ -76,6 +76,20 @@ where
     }
 }
 
+/// `ISL_YAML_STYLE_BLOCK` from `isl/printer.h`; bindgen does not pick up the define.
+pub(crate) const ISL_YAML_STYLE_BLOCK: std::ffi::c_int = 0;
+/// `ISL_FORMAT_C` from `isl/printer.h`.
+pub(crate) const ISL_FORMAT_C: std::ffi::c_int = 4;
+
+/// Take ownership of a `malloc`ed string returned by ISL.
+pub(crate) unsafe fn take_isl_string(ptr: *mut c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    let mstring = unsafe { MallocCString::from_raw(ptr) };
+    Some(mstring.to_string_lossy().into_owned())
+}
+
 #[repr(transparent)]
 pub(crate) struct Printer(NonNull<barvinok_sys::isl_printer>);
 impl Printer {
@@ -86,6 +100,12 @@ impl Printer {
     pub(crate) fn as_ptr(&self) -> *mut barvinok_sys::isl_printer {
         self.0.as_ptr()
     }
+    pub(crate) unsafe fn from_raw(printer: *mut barvinok_sys::isl_printer) -> Option<Self> {
+        NonNull::new(printer).map(Self)
+    }
+    pub(crate) fn into_raw(self) -> *mut barvinok_sys::isl_printer {
+        ManuallyDrop::new(self).0.as_ptr()
+    }
     pub(crate) unsafe fn transform<T>(
         self,
         func: unsafe extern "C" fn(
@@ -98,6 +118,9 @@ impl Printer {
         let printer = unsafe { func(this.0.as_ptr(), data) };
         NonNull::new(printer).map(Self)
     }
+    pub(crate) fn get_string(&self) -> Option<String> {
+        unsafe { take_isl_string(barvinok_sys::isl_printer_get_str(self.0.as_ptr())) }
+    }
 }
 
 impl Drop for Printer {
@@ -0,0 +1,476 @@
+use std::{mem::ManuallyDrop, ptr::NonNull};
+
+use crate::{
+    aff::MultiUnionPiecewiseAffine,
+    callback::Callback,
+    ident::Ident,
+    impl_isl_handle, isl_ctor, isl_flag, isl_project, isl_size, isl_transform,
+    list::List,
+    printer::{ISL_YAML_STYLE_BLOCK, Printer},
+    set::Set,
+    space::Space,
+    stat::{isl_bool_to_optional_bool, isl_size_to_optional_u32},
+    union_map::UnionMap,
+    union_set::UnionSet,
+    value::MultiValue,
+};
+
+impl_isl_handle!(Schedule, schedule);
+impl_isl_handle!(ScheduleNode, schedule_node);
+impl_isl_handle!(ScheduleConstraints, schedule_constraints);
+
+type UnionSetList<'a> = List<'a, UnionSet<'a>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ScheduleNodeType {
+    Band = barvinok_sys::isl_schedule_node_type_isl_schedule_node_band,
+    Context = barvinok_sys::isl_schedule_node_type_isl_schedule_node_context,
+    Domain = barvinok_sys::isl_schedule_node_type_isl_schedule_node_domain,
+    Expansion = barvinok_sys::isl_schedule_node_type_isl_schedule_node_expansion,
+    Extension = barvinok_sys::isl_schedule_node_type_isl_schedule_node_extension,
+    Filter = barvinok_sys::isl_schedule_node_type_isl_schedule_node_filter,
+    Leaf = barvinok_sys::isl_schedule_node_type_isl_schedule_node_leaf,
+    Guard = barvinok_sys::isl_schedule_node_type_isl_schedule_node_guard,
+    Mark = barvinok_sys::isl_schedule_node_type_isl_schedule_node_mark,
+    Sequence = barvinok_sys::isl_schedule_node_type_isl_schedule_node_sequence,
+    Set = barvinok_sys::isl_schedule_node_type_isl_schedule_node_set,
+}
+
+impl ScheduleNodeType {
+    fn from_raw(value: barvinok_sys::isl_schedule_node_type) -> Option<Self> {
+        match value {
+            barvinok_sys::isl_schedule_node_type_isl_schedule_node_band => Some(Self::Band),
+            barvinok_sys::isl_schedule_node_type_isl_schedule_node_context => Some(Self::Context),
+            barvinok_sys::isl_schedule_node_type_isl_schedule_node_domain => Some(Self::Domain),
+            barvinok_sys::isl_schedule_node_type_isl_schedule_node_expansion => {
+                Some(Self::Expansion)
+            }
+            barvinok_sys::isl_schedule_node_type_isl_schedule_node_extension => {
+                Some(Self::Extension)
+            }
+            barvinok_sys::isl_schedule_node_type_isl_schedule_node_filter => Some(Self::Filter),
+            barvinok_sys::isl_schedule_node_type_isl_schedule_node_leaf => Some(Self::Leaf),
+            barvinok_sys::isl_schedule_node_type_isl_schedule_node_guard => Some(Self::Guard),
+            barvinok_sys::isl_schedule_node_type_isl_schedule_node_mark => Some(Self::Mark),
+            barvinok_sys::isl_schedule_node_type_isl_schedule_node_sequence => Some(Self::Sequence),
+            barvinok_sys::isl_schedule_node_type_isl_schedule_node_set => Some(Self::Set),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ScheduleConstraints<'a> {
+    isl_ctor!(on_domain, isl_schedule_constraints_on_domain, domain: UnionSet<'a>);
+    isl_ctor!([ctx] from_str, isl_schedule_constraints_read_from_str, [str] str: &str);
+    isl_transform!(set_context, isl_schedule_constraints_set_context, [managed] context: Set<'a>);
+    isl_transform!(set_validity, isl_schedule_constraints_set_validity, [managed] validity: UnionMap<'a>);
+    isl_transform!(set_coincidence, isl_schedule_constraints_set_coincidence, [managed] coincidence: UnionMap<'a>);
+    isl_transform!(set_proximity, isl_schedule_constraints_set_proximity, [managed] proximity: UnionMap<'a>);
+    isl_transform!(set_conditional_validity, isl_schedule_constraints_set_conditional_validity, [managed] condition: UnionMap<'a>, [managed] validity: UnionMap<'a>);
+    isl_transform!(apply, isl_schedule_constraints_apply, [managed] map: UnionMap<'a>);
+    isl_project!([into(UnionSet)] get_domain, isl_schedule_constraints_get_domain);
+    isl_project!([into(Set)] get_context, isl_schedule_constraints_get_context);
+    isl_project!([into(UnionMap)] get_validity, isl_schedule_constraints_get_validity);
+    isl_project!([into(UnionMap)] get_coincidence, isl_schedule_constraints_get_coincidence);
+    isl_project!([into(UnionMap)] get_proximity, isl_schedule_constraints_get_proximity);
+    isl_project!([into(UnionMap)] get_conditional_validity, isl_schedule_constraints_get_conditional_validity);
+    isl_project!([into(UnionMap)] get_conditional_validity_condition, isl_schedule_constraints_get_conditional_validity_condition);
+    isl_transform!([into(Schedule)] compute_schedule, isl_schedule_constraints_compute_schedule);
+}
+
+impl<'a> Schedule<'a> {
+    isl_ctor!(from_domain, isl_schedule_from_domain, domain: UnionSet<'a>);
+    isl_ctor!(empty, isl_schedule_empty, space: Space<'a>);
+    isl_ctor!([ctx] from_str, isl_schedule_read_from_str, [str] str: &str);
+    isl_project!([into(UnionMap)] get_map, isl_schedule_get_map);
+    isl_project!([into(ScheduleNode)] get_root, isl_schedule_get_root);
+    isl_project!([into(UnionSet)] get_domain, isl_schedule_get_domain);
+    isl_flag!(schedule_plain_is_equal => plain_is_equal, [ref] other: &Schedule<'a>);
+    isl_transform!(insert_context, isl_schedule_insert_context, [managed] context: Set<'a>);
+    isl_transform!(insert_partial_schedule, isl_schedule_insert_partial_schedule, [managed] partial: MultiUnionPiecewiseAffine<'a>);
+    isl_transform!(insert_guard, isl_schedule_insert_guard, [managed] guard: Set<'a>);
+    isl_transform!(sequence, isl_schedule_sequence, [managed] other: Schedule<'a>);
+    isl_transform!(set, isl_schedule_set, [managed] other: Schedule<'a>);
+    isl_transform!(intersect_domain, isl_schedule_intersect_domain, [managed] domain: UnionSet<'a>);
+    isl_transform!(gist_domain_params, isl_schedule_gist_domain_params, [managed] context: Set<'a>);
+    isl_transform!(align_params, isl_schedule_align_params, [managed] space: Space<'a>);
+    isl_transform!(reset_user, isl_schedule_reset_user);
+
+    /// Print the schedule tree in YAML block style.
+    /// The [`Debug`](std::fmt::Debug) output uses the flow style instead.
+    /// Both forms can be read back with [`Schedule::from_str`].
+    pub fn to_block_yaml(&self) -> Result<String, crate::Error> {
+        let ctx = self.context_ref();
+        let string = unsafe {
+            Printer::new(ctx.0)
+                .and_then(|p| {
+                    p.transform(
+                        barvinok_sys::isl_printer_set_yaml_style,
+                        ISL_YAML_STYLE_BLOCK,
+                    )
+                })
+                .and_then(|p| {
+                    p.transform(
+                        barvinok_sys::isl_printer_print_schedule,
+                        self.handle.as_ptr(),
+                    )
+                })
+                .and_then(|p| p.get_string())
+        };
+        string.ok_or_else(|| ctx.last_error_or_unknown().into())
+    }
+}
+
+impl<'a> ScheduleNode<'a> {
+    isl_ctor!(from_domain, isl_schedule_node_from_domain, domain: UnionSet<'a>);
+    isl_ctor!(from_extension, isl_schedule_node_from_extension, extension: UnionMap<'a>);
+    isl_flag!(schedule_node_is_equal => checked_eq, [ref] other: &ScheduleNode<'a>);
+    isl_project!([into(Schedule)] get_schedule, isl_schedule_node_get_schedule);
+
+    pub fn get_type(&self) -> Result<ScheduleNodeType, crate::Error> {
+        let ty = unsafe { barvinok_sys::isl_schedule_node_get_type(self.handle.as_ptr()) };
+        ScheduleNodeType::from_raw(ty)
+            .ok_or_else(|| self.context_ref().last_error_or_unknown().into())
+    }
+    pub fn get_parent_type(&self) -> Result<ScheduleNodeType, crate::Error> {
+        let ty = unsafe { barvinok_sys::isl_schedule_node_get_parent_type(self.handle.as_ptr()) };
+        ScheduleNodeType::from_raw(ty)
+            .ok_or_else(|| self.context_ref().last_error_or_unknown().into())
+    }
+
+    isl_size!(schedule_node_get_tree_depth => get_tree_depth);
+    isl_size!(schedule_node_get_schedule_depth => get_schedule_depth);
+    isl_size!(schedule_node_n_children => num_children);
+    isl_size!(schedule_node_get_child_position => get_child_position);
+    isl_size!(schedule_node_get_ancestor_child_position => get_ancestor_child_position, [ref] ancestor: &ScheduleNode<'a>);
+    isl_flag!(schedule_node_has_parent => has_parent);
+    isl_flag!(schedule_node_has_children => has_children);
+    isl_flag!(schedule_node_has_previous_sibling => has_previous_sibling);
+    isl_flag!(schedule_node_has_next_sibling => has_next_sibling);
+    isl_flag!(schedule_node_is_subtree_anchored => is_subtree_anchored);
+    isl_project!([into(ScheduleNode)] get_child, isl_schedule_node_get_child, [cast(i32)] pos: u32);
+    isl_project!([into(ScheduleNode)] get_shared_ancestor, isl_schedule_node_get_shared_ancestor, [ref] other: &ScheduleNode<'a>);
+    isl_transform!(root, isl_schedule_node_root);
+    isl_transform!(parent, isl_schedule_node_parent);
+    isl_transform!(grandparent, isl_schedule_node_grandparent);
+    isl_transform!(ancestor, isl_schedule_node_ancestor, [cast(i32)] generation: u32);
+    isl_transform!(child, isl_schedule_node_child, [cast(i32)] pos: u32);
+    isl_transform!(grandchild, isl_schedule_node_grandchild, [cast(i32)] pos1: u32, [cast(i32)] pos2: u32);
+    isl_transform!(first_child, isl_schedule_node_first_child);
+    isl_transform!(previous_sibling, isl_schedule_node_previous_sibling);
+    isl_transform!(next_sibling, isl_schedule_node_next_sibling);
+
+    isl_project!([into(Space)] band_get_space, isl_schedule_node_band_get_space);
+    isl_project!([into(MultiUnionPiecewiseAffine)] band_get_partial_schedule, isl_schedule_node_band_get_partial_schedule);
+    isl_project!([into(UnionMap)] band_get_partial_schedule_union_map, isl_schedule_node_band_get_partial_schedule_union_map);
+    isl_size!(schedule_node_band_n_member => band_num_members);
+    isl_flag!(schedule_node_band_member_get_coincident => band_member_get_coincident, [cast(i32)] pos: u32);
+    isl_transform!(band_member_set_coincident, isl_schedule_node_band_member_set_coincident, [cast(i32)] pos: u32, [cast(i32)] coincident: bool);
+    isl_flag!(schedule_node_band_get_permutable => band_get_permutable);
+    isl_transform!(band_set_permutable, isl_schedule_node_band_set_permutable, [cast(i32)] permutable: bool);
+    isl_transform!(band_scale, isl_schedule_node_band_scale, [managed] factors: MultiValue<'a>);
+    isl_transform!(band_scale_down, isl_schedule_node_band_scale_down, [managed] factors: MultiValue<'a>);
+    isl_transform!(band_mod, isl_schedule_node_band_mod, [managed] factors: MultiValue<'a>);
+    isl_transform!(band_shift, isl_schedule_node_band_shift, [managed] shift: MultiUnionPiecewiseAffine<'a>);
+    isl_transform!(band_tile, isl_schedule_node_band_tile, [managed] sizes: MultiValue<'a>);
+    isl_transform!(band_sink, isl_schedule_node_band_sink);
+    isl_transform!(band_split, isl_schedule_node_band_split, [cast(i32)] pos: u32);
+
+    isl_project!([into(Set)] context_get_context, isl_schedule_node_context_get_context);
+    isl_project!([into(UnionSet)] domain_get_domain, isl_schedule_node_domain_get_domain);
+    isl_project!([into(UnionMap)] expansion_get_expansion, isl_schedule_node_expansion_get_expansion);
+    isl_project!([into(UnionMap)] extension_get_extension, isl_schedule_node_extension_get_extension);
+    isl_project!([into(UnionSet)] filter_get_filter, isl_schedule_node_filter_get_filter);
+    isl_project!([into(Set)] guard_get_guard, isl_schedule_node_guard_get_guard);
+    isl_project!([into(Ident)] mark_get_id, isl_schedule_node_mark_get_id);
+    isl_transform!(sequence_splice_child, isl_schedule_node_sequence_splice_child, [cast(i32)] pos: u32);
+    isl_transform!(
+        sequence_splice_children,
+        isl_schedule_node_sequence_splice_children
+    );
+
+    isl_project!([into(UnionSet)] get_domain, isl_schedule_node_get_domain);
+    isl_project!([into(UnionSet)] get_universe_domain, isl_schedule_node_get_universe_domain);
+    isl_project!([into(MultiUnionPiecewiseAffine)] get_prefix_schedule_multi_union_pw_aff, isl_schedule_node_get_prefix_schedule_multi_union_pw_aff);
+    isl_project!([into(UnionMap)] get_prefix_schedule_union_map, isl_schedule_node_get_prefix_schedule_union_map);
+    isl_project!([into(UnionMap)] get_prefix_schedule_relation, isl_schedule_node_get_prefix_schedule_relation);
+    isl_project!([into(UnionMap)] get_subtree_schedule_union_map, isl_schedule_node_get_subtree_schedule_union_map);
+    isl_project!([into(UnionMap)] get_subtree_expansion, isl_schedule_node_get_subtree_expansion);
+
+    isl_transform!(insert_context, isl_schedule_node_insert_context, [managed] context: Set<'a>);
+    isl_transform!(insert_partial_schedule, isl_schedule_node_insert_partial_schedule, [managed] schedule: MultiUnionPiecewiseAffine<'a>);
+    isl_transform!(insert_filter, isl_schedule_node_insert_filter, [managed] filter: UnionSet<'a>);
+    isl_transform!(insert_guard, isl_schedule_node_insert_guard, [managed] guard: Set<'a>);
+    isl_transform!(insert_mark, isl_schedule_node_insert_mark, [managed] mark: Ident<'a>);
+    isl_transform!(insert_sequence, isl_schedule_node_insert_sequence, [managed] filters: UnionSetList<'a>);
+    isl_transform!(insert_set, isl_schedule_node_insert_set, [managed] filters: UnionSetList<'a>);
+    isl_transform!(cut, isl_schedule_node_cut);
+    isl_transform!(delete, isl_schedule_node_delete);
+    isl_transform!(group, isl_schedule_node_group, [managed] group_id: Ident<'a>);
+    isl_transform!(order_before, isl_schedule_node_order_before, [managed] filter: UnionSet<'a>);
+    isl_transform!(order_after, isl_schedule_node_order_after, [managed] filter: UnionSet<'a>);
+    isl_transform!(graft_before, isl_schedule_node_graft_before, [managed] graft: ScheduleNode<'a>);
+    isl_transform!(graft_after, isl_schedule_node_graft_after, [managed] graft: ScheduleNode<'a>);
+    isl_transform!(align_params, isl_schedule_node_align_params, [managed] space: Space<'a>);
+    isl_transform!(reset_user, isl_schedule_node_reset_user);
+
+    /// Visit this node and its descendants in depth-first pre-order.
+    /// Returning `Ok(false)` from `func` skips the children of the visited node.
+    pub fn foreach_descendant_top_down<F>(&self, func: F) -> Result<(), crate::Error>
+    where
+        F: FnMut(&ScheduleNode<'a>) -> Result<bool, crate::Error>,
+    {
+        unsafe extern "C" fn callback<'a, F>(
+            node: *mut barvinok_sys::isl_schedule_node,
+            user: *mut std::ffi::c_void,
+        ) -> barvinok_sys::isl_bool
+        where
+            F: FnMut(&ScheduleNode<'a>) -> Result<bool, crate::Error>,
+        {
+            let Some(node) = NonNull::new(node) else {
+                return barvinok_sys::isl_bool_isl_bool_error;
+            };
+            let state = unsafe { Callback::<F>::from_user(user) };
+            // the node is only borrowed by the callback
+            let node = ManuallyDrop::new(ScheduleNode {
+                handle: node,
+                marker: std::marker::PhantomData,
+            });
+            state.run_bool(|func| func(&node))
+        }
+        let mut state = Callback::new(func);
+        let res = unsafe {
+            barvinok_sys::isl_schedule_node_foreach_descendant_top_down(
+                self.handle.as_ptr(),
+                Some(callback::<F>),
+                state.as_user(),
+            )
+        };
+        state.finish(
+            res != barvinok_sys::isl_stat_isl_stat_ok,
+            self.context_ref(),
+        )
+    }
+
+    /// Replace every descendant of this node (including itself) by the result of `func`,
+    /// visiting children before their parents.
+    pub fn map_descendant_bottom_up<F>(self, func: F) -> Result<Self, crate::Error>
+    where
+        F: FnMut(ScheduleNode<'a>) -> Result<ScheduleNode<'a>, crate::Error>,
+    {
+        unsafe extern "C" fn callback<'a, F>(
+            node: *mut barvinok_sys::isl_schedule_node,
+            user: *mut std::ffi::c_void,
+        ) -> *mut barvinok_sys::isl_schedule_node
+        where
+            F: FnMut(ScheduleNode<'a>) -> Result<ScheduleNode<'a>, crate::Error>,
+        {
+            let Some(node) = NonNull::new(node) else {
+                return std::ptr::null_mut();
+            };
+            let state = unsafe { Callback::<F>::from_user(user) };
+            let node = ScheduleNode {
+                handle: node,
+                marker: std::marker::PhantomData,
+            };
+            match state.run(|func| func(node)) {
+                Some(node) => ManuallyDrop::new(node).handle.as_ptr(),
+                None => std::ptr::null_mut(),
+            }
+        }
+        let ctx = self.context_ref();
+        let this = ManuallyDrop::new(self);
+        let mut state = Callback::new(func);
+        let res = unsafe {
+            barvinok_sys::isl_schedule_node_map_descendant_bottom_up(
+                this.handle.as_ptr(),
+                Some(callback::<F>),
+                state.as_user(),
+            )
+        };
+        let node = NonNull::new(res).map(|handle| ScheduleNode {
+            handle,
+            marker: std::marker::PhantomData,
+        });
+        state.finish(node.is_none(), ctx)?;
+        node.ok_or_else(|| ctx.last_error_or_unknown().into())
+    }
+}
+
+impl<'a> UnionSet<'a> {
+    isl_transform!([into(Schedule)] compute_schedule, isl_union_set_compute_schedule, [managed] validity: UnionMap<'a>, [managed] proximity: UnionMap<'a>);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+
+    #[test]
+    fn test_schedule_from_domain() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let domain = UnionSet::from_str(ctx, "[n] -> { S[i, j] : 0 <= i, j < n }")?;
+            let schedule = Schedule::from_domain(domain.clone())?;
+            let root = schedule.get_root()?;
+            assert_eq!(root.get_type()?, ScheduleNodeType::Domain);
+            assert_eq!(root.domain_get_domain()?, domain);
+            let leaf = root.first_child()?;
+            assert_eq!(leaf.get_type()?, ScheduleNodeType::Leaf);
+            assert!(!leaf.has_children()?);
+
+            let partial = MultiUnionPiecewiseAffine::from_str(
+                ctx,
+                "[n] -> [{ S[i, j] -> [(j)] }, { S[i, j] -> [(i)] }]",
+            )?;
+            let band = leaf.insert_partial_schedule(partial)?;
+            assert_eq!(band.get_type()?, ScheduleNodeType::Band);
+            assert_eq!(band.band_num_members()?, 2);
+            let map = band.get_schedule()?.get_map()?.intersect_domain(domain)?;
+            let expected = UnionMap::from_str(ctx, "[n] -> { S[i, j] -> [j, i] : 0 <= i, j < n }")?;
+            assert_eq!(map, expected);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_schedule_tile_and_mark() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let domain = UnionSet::from_str(ctx, "{ S[i, j] : 0 <= i, j < 128 }")?;
+            let partial = MultiUnionPiecewiseAffine::from_str(
+                ctx,
+                "[{ S[i, j] -> [(i)] }, { S[i, j] -> [(j)] }]",
+            )?;
+            let schedule = Schedule::from_domain(domain)?.insert_partial_schedule(partial)?;
+            let band = schedule.get_root()?.first_child()?;
+            let band = band.band_set_permutable(true)?;
+            assert!(band.band_get_permutable()?);
+            let sizes = MultiValue::from_str(ctx, "{ [32, 16] }")?;
+            let tiled = band.band_tile(sizes)?;
+            assert_eq!(tiled.get_type()?, ScheduleNodeType::Band);
+            let point = tiled.first_child()?;
+            assert_eq!(point.get_type()?, ScheduleNodeType::Band);
+            let marked = point.insert_mark(Ident::new(ctx, "point_loops")?)?;
+            assert_eq!(marked.get_type()?, ScheduleNodeType::Mark);
+            assert_eq!(marked.mark_get_id()?.name()?, "point_loops");
+            assert_eq!(marked.get_tree_depth()?, 2);
+
+            let mut kinds = Vec::new();
+            marked.root()?.foreach_descendant_top_down(|node| {
+                kinds.push(node.get_type()?);
+                Ok(true)
+            })?;
+            assert_eq!(
+                kinds,
+                [
+                    ScheduleNodeType::Domain,
+                    ScheduleNodeType::Band,
+                    ScheduleNodeType::Mark,
+                    ScheduleNodeType::Band,
+                    ScheduleNodeType::Leaf,
+                ]
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_schedule_sequence_and_filter() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let domain = UnionSet::from_str(ctx, "{ S[i] : 0 <= i < 10; T[i] : 0 <= i < 10 }")?;
+            let mut filters = List::new(ctx, 2);
+            filters.push(UnionSet::from_str(ctx, "{ T[i] }")?);
+            filters.push(UnionSet::from_str(ctx, "{ S[i] }")?);
+            let node = Schedule::from_domain(domain.clone())?
+                .get_root()?
+                .first_child()?;
+            let sequence = node.insert_sequence(filters)?;
+            assert_eq!(sequence.get_type()?, ScheduleNodeType::Sequence);
+            assert_eq!(sequence.num_children()?, 2);
+            let second = sequence.child(1)?;
+            assert_eq!(second.get_type()?, ScheduleNodeType::Filter);
+            assert_eq!(second.get_child_position()?, 1);
+            assert!(second.has_previous_sibling()?);
+            assert!(!second.has_next_sibling()?);
+            let filter = second.filter_get_filter()?;
+            assert_eq!(filter, UnionSet::from_str(ctx, "{ S[i] }")?);
+            let map = second.get_schedule()?.get_map()?.intersect_domain(domain)?;
+            let expected = UnionMap::from_str(
+                ctx,
+                "{ T[i] -> [0]: 0 <= i < 10; S[i] -> [1] : 0 <= i < 10 }",
+            )?;
+            assert_eq!(map, expected);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_compute_schedule() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let domain =
+                UnionSet::from_str(ctx, "[n] -> { S[i] : 0 <= i < n; T[i] : 0 <= i < n }")?;
+            let dependences = UnionMap::from_str(ctx, "[n] -> { S[i] -> T[i]; T[i] -> S[i + 1] }")?;
+            let constraints = ScheduleConstraints::on_domain(domain)?
+                .set_validity(dependences.clone())?
+                .set_proximity(dependences.clone())?
+                .set_coincidence(UnionMap::from_str(ctx, "[n] -> { S[i] -> T[i] }")?)?;
+            assert_eq!(constraints.get_validity()?, dependences);
+            let schedule = constraints.compute_schedule()?;
+            println!("{:?}", schedule);
+            let map = schedule.get_map()?;
+            let ordered = map.clone().lex_lt_union_map(map)?;
+            let dependences = dependences.intersect_domain(schedule.get_domain()?)?;
+            let dependences = dependences.intersect_range(schedule.get_domain()?)?;
+            assert!(dependences.subset(&ordered)?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_schedule_yaml_roundtrip() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let schedule = Schedule::from_str(
+                ctx,
+                "{ domain: \"{ S[i] : 0 <= i < 10 }\", child: { schedule: \"[{ S[i] -> [(i)] }]\" } }",
+            )?;
+            let yaml = schedule.to_block_yaml()?;
+            assert!(yaml.starts_with("domain:"));
+            assert!(yaml.contains("\nchild:\n"));
+            let parsed = Schedule::from_str(ctx, &yaml)?;
+            assert!(parsed.plain_is_equal(&schedule)?);
+            let flow = format!("{:?}", schedule);
+            let parsed = Schedule::from_str(ctx, &flow)?;
+            assert!(parsed.plain_is_equal(&schedule)?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_map_descendant_bottom_up() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let domain = UnionSet::from_str(ctx, "{ S[i, j] : 0 <= i, j < 8 }")?;
+            let partial = MultiUnionPiecewiseAffine::from_str(
+                ctx,
+                "[{ S[i, j] -> [(i)] }, { S[i, j] -> [(j)] }]",
+            )?;
+            let schedule = Schedule::from_domain(domain)?.insert_partial_schedule(partial)?;
+            let root = schedule.get_root()?.map_descendant_bottom_up(|node| {
+                if node.get_type()? == ScheduleNodeType::Band {
+                    node.band_member_set_coincident(0, true)
+                } else {
+                    Ok(node)
+                }
+            })?;
+            let band = root.first_child()?;
+            assert!(band.band_member_get_coincident(0)?);
+            assert!(!band.band_member_get_coincident(1)?);
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,276 @@
+//! Serde support, enabled by the `serde` feature.
+//!
+//! ISL objects serialize to their textual ISL representation and [`Value`]s to their exact
+//! rational form, such as `-7/3`. Identifiers serialize to their name. Objects always belong
+//! to a context, so they are deserialized with [`InContext`], a [`DeserializeSeed`] that
+//! carries the target context.
+//!
+//! [`Formula`](crate::polynomial::Formula) gives a structured form of piecewise
+//! quasi-polynomials for consumers that cannot parse ISL syntax.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    Deserializer, Serialize, Serializer,
+    de::{self, DeserializeSeed, Visitor},
+};
+
+use crate::{
+    ContextRef,
+    aff::{Affine, MultiAffine, MultiUnionPiecewiseAffine, PiecewiseAffine, PiecewiseMultiAffine},
+    ast::{AstExpr, AstNode},
+    constraint::Constraint,
+    fixed_box::FixedBox,
+    ident::Ident,
+    local_space::LocalSpace,
+    map::{BasicMap, Map},
+    point::Point,
+    polynomial::{PiecewiseQuasiPolynomial, QuasiPolynomial},
+    schedule::{Schedule, ScheduleConstraints, ScheduleNode},
+    set::{BasicSet, Set},
+    space::Space,
+    traits::IslPrintable,
+    union_map::UnionMap,
+    union_set::UnionSet,
+    value::{MultiValue, Value},
+    vec::Vector,
+};
+
+/// ISL objects that can be parsed back from their serialized text.
+pub trait FromIslStr<'a>: Sized {
+    fn from_isl_str(ctx: ContextRef<'a>, text: &str) -> Result<Self, crate::Error>;
+}
+
+macro_rules! impl_serialize {
+    ($($type:ident),* $(,)?) => {
+        $(
+            impl Serialize for $type<'_> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(&self.to_isl_string())
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_from_isl_str {
+    ($($type:ident),* $(,)?) => {
+        $(
+            impl<'a> FromIslStr<'a> for $type<'a> {
+                fn from_isl_str(ctx: ContextRef<'a>, text: &str) -> Result<Self, crate::Error> {
+                    $type::from_str(ctx, text)
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize!(
+    Set,
+    BasicSet,
+    Map,
+    BasicMap,
+    UnionSet,
+    UnionMap,
+    Affine,
+    MultiAffine,
+    PiecewiseAffine,
+    PiecewiseMultiAffine,
+    MultiUnionPiecewiseAffine,
+    QuasiPolynomial,
+    PiecewiseQuasiPolynomial,
+    Value,
+    MultiValue,
+    Space,
+    LocalSpace,
+    Constraint,
+    Point,
+    Vector,
+    FixedBox,
+    Schedule,
+    ScheduleNode,
+    ScheduleConstraints,
+    AstExpr,
+    AstNode,
+);
+
+impl_from_isl_str!(
+    Set,
+    BasicSet,
+    Map,
+    BasicMap,
+    UnionSet,
+    UnionMap,
+    Affine,
+    MultiAffine,
+    PiecewiseAffine,
+    PiecewiseMultiAffine,
+    MultiUnionPiecewiseAffine,
+    PiecewiseQuasiPolynomial,
+    MultiValue,
+    Space,
+    FixedBox,
+    Schedule,
+    ScheduleConstraints,
+);
+
+impl<'a> FromIslStr<'a> for Value<'a> {
+    fn from_isl_str(ctx: ContextRef<'a>, text: &str) -> Result<Self, crate::Error> {
+        Value::new_from_string(ctx, text)
+    }
+}
+
+impl<'a> FromIslStr<'a> for QuasiPolynomial<'a> {
+    fn from_isl_str(ctx: ContextRef<'a>, text: &str) -> Result<Self, crate::Error> {
+        // ISL cannot parse a bare quasi-polynomial, so read it as a single piece.
+        PiecewiseQuasiPolynomial::from_str(ctx, text)?.as_qpolynomial()
+    }
+}
+
+/// The printed form of an identifier includes its user data address, so only the name is
+/// serialized.
+impl Serialize for Ident<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name().map_err(serde::ser::Error::custom)?)
+    }
+}
+
+impl<'a> FromIslStr<'a> for Ident<'a> {
+    fn from_isl_str(ctx: ContextRef<'a>, text: &str) -> Result<Self, crate::Error> {
+        Ident::new(ctx, text)
+    }
+}
+
+/// Deserializes a `T` into the context `ctx`.
+///
+/// ```ignore
+/// let set: Set = InContext::new(ctx).deserialize(&mut serde_json::Deserializer::from_str(json))?;
+/// ```
+pub struct InContext<'a, T> {
+    ctx: ContextRef<'a>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> InContext<'a, T> {
+    pub fn new(ctx: ContextRef<'a>) -> Self {
+        Self {
+            ctx,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for InContext<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for InContext<'_, T> {}
+
+impl<'de, 'a, T: FromIslStr<'a>> DeserializeSeed<'de> for InContext<'a, T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'a, T: FromIslStr<'a>> Visitor<'_> for InContext<'a, T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an object in ISL syntax")
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<T, E> {
+        T::from_isl_str(self.ctx, text).map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+
+    fn round_trip<'a, T: Serialize + FromIslStr<'a>>(
+        ctx: ContextRef<'a>,
+        object: &T,
+    ) -> anyhow::Result<T> {
+        let json = serde_json::to_string(object)?;
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        Ok(InContext::new(ctx).deserialize(&mut deserializer)?)
+    }
+
+    #[test]
+    fn test_round_trip() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "[n] -> { A[i] : 0 <= i < n }")?;
+            assert_eq!(
+                serde_json::to_string(&set)?,
+                r#""[n] -> { A[i] : 0 <= i < n }""#
+            );
+            assert_eq!(round_trip(ctx, &set)?, set);
+
+            let umap = UnionMap::from_str(ctx, "{ A[i] -> B[i + 1]; B[i] -> A[i] }")?;
+            assert_eq!(round_trip(ctx, &umap)?, umap);
+
+            let value = Value::new_from_string(ctx, "-7/3")?;
+            assert_eq!(serde_json::to_string(&value)?, r#""-7/3""#);
+            assert_eq!(round_trip(ctx, &value)?, value);
+
+            let pwqp =
+                PiecewiseQuasiPolynomial::from_str(ctx, "[n] -> { [i] -> n * i : 0 <= i < n }")?;
+            let back = round_trip(ctx, &pwqp)?;
+            assert!((back - pwqp)?.is_zero()?);
+
+            let id = Ident::new_with_user(ctx, "x", 1)?;
+            assert_eq!(serde_json::to_string(&id)?, r#""x""#);
+            assert_eq!(round_trip(ctx, &id)?.name()?, "x");
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let mut deserializer = serde_json::Deserializer::from_str(r#""{ [i] : ""#);
+            let set: Result<Set, _> = InContext::new(ctx).deserialize(&mut deserializer);
+            assert!(set.is_err());
+            let mut deserializer = serde_json::Deserializer::from_str("42");
+            let set: Result<Set, _> = InContext::new(ctx).deserialize(&mut deserializer);
+            assert!(set.is_err());
+        });
+    }
+
+    #[test]
+    fn test_formula_json() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let pwqp = PiecewiseQuasiPolynomial::from_str(
+                ctx,
+                "[n] -> { [i] -> 3/2 * n * i^2 : 0 <= i < n }",
+            )?;
+            let json = serde_json::to_value(pwqp.to_formula()?)?;
+            assert_eq!(
+                json,
+                serde_json::json!({
+                    "params": ["n"],
+                    "variables": ["i"],
+                    "pieces": [{
+                        "domain": "[n] -> { [i] : 0 <= i < n }",
+                        "terms": [{
+                            "coefficient": "3/2",
+                            "params": [1],
+                            "variables": [2],
+                            "divs": [],
+                        }],
+                    }],
+                })
+            );
+            Ok(())
+        })
+    }
+}
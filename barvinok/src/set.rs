@@ -1,7 +1,9 @@
-use std::{cell::Cell, mem::ManuallyDrop, ptr::NonNull};
+use std::{mem::ManuallyDrop, ptr::NonNull};
 
 use crate::{
     DimType,
+    aff::{MultiAffine, PiecewiseMultiAffine},
+    callback::{Callback, IterationControl},
     constraint::Constraint,
     ident::Ident,
     impl_isl_handle, isl_ctor, isl_flag, isl_project, isl_size, isl_str, isl_transform,
@@ -22,9 +24,9 @@ macro_rules! set_named_id_methods {
             impl<'a> $Wrapper<'a> {
                 isl_str!([<$ctype _get_tuple_name>] => [<get_tuple_name>]);
                 isl_transform!(set_tuple_name, [<isl_ $ctype _set_tuple_name>], [str] name : &str);
-                isl_str!([<$ctype _get_dim_name>] => [<get_dim_name>], [cast(u32)] ty : DimType, [trivial] pos : u32);
-                isl_transform!(set_dim_name, [<isl_ $ctype _set_dim_name>], [cast(u32)] ty : DimType, [trivial] pos : u32, [str] name : &str);
-                isl_project!([into(Ident)] get_dim_id, [<isl_ $ctype _get_dim_id>], [cast(u32)] ty : DimType, [trivial] pos : u32);
+                isl_str!([<$ctype _get_dim_name>] => [<get_dim_name>], dim: Dim);
+                isl_transform!(set_dim_name, [<isl_ $ctype _set_dim_name>], dim: Dim, [str] name : &str);
+                isl_project!([into(Ident)] get_dim_id, [<isl_ $ctype _get_dim_id>], dim: Dim);
                 isl_transform!(set_tuple_id, [<isl_ $ctype _set_tuple_id>], [managed] id : Ident<'a>);
             }
         }
@@ -33,7 +35,38 @@ macro_rules! set_named_id_methods {
 
 set_named_id_methods!(BasicSet, basic_set);
 set_named_id_methods!(Set, set);
+
+macro_rules! set_dim_methods {
+    ($Wrapper:ident, $ctype:ident) => {
+        paste::paste! {
+            impl<'a> $Wrapper<'a> {
+                isl_transform!(project_out, [<isl_ $ctype _project_out>], [cast(u32)] ty : DimType, [trivial] first : u32, [trivial] num : u32);
+                isl_transform!(eliminate, [<isl_ $ctype _eliminate>], [cast(u32)] ty : DimType, [trivial] first : u32, [trivial] num : u32);
+                isl_transform!(params, [<isl_ $ctype _params>]);
+                isl_transform!(from_params, [<isl_ $ctype _from_params>]);
+                isl_transform!(fix_si, [<isl_ $ctype _fix_si>], dim: Dim, [trivial] value : i32);
+                isl_transform!(fix_val, [<isl_ $ctype _fix_val>], dim: Dim, [managed] value : Value<'a>);
+                isl_transform!(lower_bound_val, [<isl_ $ctype _lower_bound_val>], dim: Dim, [managed] value : Value<'a>);
+                isl_transform!(upper_bound_val, [<isl_ $ctype _upper_bound_val>], dim: Dim, [managed] value : Value<'a>);
+                isl_transform!(add_dims, [<isl_ $ctype _add_dims>], [cast(u32)] ty : DimType, [trivial] num : u32);
+                isl_transform!(drop_unused_params, [<isl_ $ctype _drop_unused_params>]);
+                isl_transform!(align_params, [<isl_ $ctype _align_params>], [managed] model : Space<'a>);
+                isl_transform!(preimage_multi_aff, [<isl_ $ctype _preimage_multi_aff>], [managed] ma : MultiAffine<'a>);
+                isl_transform!(flatten, [<isl_ $ctype _flatten>]);
+                isl_flag!([<$ctype _is_wrapping>] => is_wrapping);
+                isl_transform!(remove_divs, [<isl_ $ctype _remove_divs>]);
+                isl_transform!(remove_unknown_divs, [<isl_ $ctype _remove_unknown_divs>]);
+                isl_transform!(remove_divs_involving_dims, [<isl_ $ctype _remove_divs_involving_dims>], [cast(u32)] ty : DimType, [trivial] first : u32, [trivial] num : u32);
+                isl_transform!(drop_constraints_involving_dims, [<isl_ $ctype _drop_constraints_involving_dims>], [cast(u32)] ty : DimType, [trivial] first : u32, [trivial] num : u32);
+            }
+        }
+    };
+}
+
+set_dim_methods!(BasicSet, basic_set);
+set_dim_methods!(Set, set);
 type ConstraintList<'a> = List<'a, Constraint<'a>>;
+type BasicSetList<'a> = List<'a, BasicSet<'a>>;
 #[allow(clippy::should_implement_trait)]
 impl<'a> BasicSet<'a> {
     isl_ctor!(universe, isl_basic_set_universe, space : Space<'a>);
@@ -61,11 +94,25 @@ impl<'a> BasicSet<'a> {
     isl_transform!([into(Set)] lexmax, isl_basic_set_lexmax);
     isl_flag!(basic_set_is_equal => checked_eq, [ref] other : &BasicSet<'a>);
     isl_flag!(basic_set_is_disjoint => disjoint, [ref] other : &BasicSet<'a>);
+    isl_flag!(basic_set_is_subset => subset, [ref] other : &BasicSet<'a>);
     isl_transform!([into(Set)] union, isl_basic_set_union, [managed] other : BasicSet<'a>);
     isl_transform!(flat_product, isl_basic_set_flat_product, [managed] other : BasicSet<'a>);
     isl_transform!(checked_neg, isl_basic_set_neg);
     isl_transform!([into(Set)] compute_divs, isl_basic_set_compute_divs);
     isl_transform!(gist, isl_basic_set_gist, [managed] context : BasicSet<'a>);
+    isl_transform!(insert_dims, isl_basic_set_insert_dims, [cast(u32)] ty : DimType, [trivial] pos : u32, [trivial] num : u32);
+    isl_transform!(move_dims, isl_basic_set_move_dims, [cast(u32)] dst_dim_type : DimType, [trivial] dst_pos : u32, [cast(u32)] src_dim_type : DimType, [trivial] src_pos : u32, [trivial] num : u32);
+    isl_transform!([into(BasicMap)] unwrap, isl_basic_set_unwrap);
+
+    /// The difference of two basic sets is in general not convex.
+    pub fn subtract(self, other: BasicSet<'a>) -> Result<Set<'a>, crate::Error> {
+        Set::try_from(self)?.subtract(Set::try_from(other)?)
+    }
+
+    /// The complement is taken within the universe of the space, so it is a [`Set`].
+    pub fn complement(self) -> Result<Set<'a>, crate::Error> {
+        Set::try_from(self)?.complement()
+    }
 }
 #[allow(clippy::should_implement_trait)]
 impl<'a> Set<'a> {
@@ -100,8 +147,8 @@ impl<'a> Set<'a> {
     isl_project!([into(Space)] get_space, isl_set_get_space);
     isl_transform!(reset_space, isl_set_reset_space, [managed] space : Space<'a>);
     isl_flag!(set_has_tuple_name => has_tuple_name);
-    isl_flag!(set_has_dim_name => has_dim_name, [cast(u32)] ty : DimType, [trivial] pos : u32);
-    isl_flag!(set_has_dim_id => has_dim_id, [cast(u32)] ty : DimType, [trivial] pos : u32);
+    isl_flag!(set_has_dim_name => has_dim_name, dim: Dim);
+    isl_flag!(set_has_dim_id => has_dim_id, dim: Dim);
     isl_flag!(set_has_tuple_id => has_tuple_id);
     isl_project!([into(Ident)] get_tuple_id, isl_set_get_tuple_id);
     isl_transform!(reset_tuple_id, isl_set_reset_tuple_id);
@@ -133,17 +180,18 @@ impl<'a> Set<'a> {
     isl_transform!(checked_neg, isl_set_neg);
     isl_transform!(make_disjoint, isl_set_make_disjoint);
     isl_transform!(compute_divs, isl_set_compute_divs);
-    isl_flag!(set_dim_is_bounded => dim_is_bounded, [cast(u32)] ty : DimType, [trivial] pos : u32);
-    isl_flag!(set_dim_has_lower_bound => dim_has_lower_bound, [cast(u32)] ty : DimType, [trivial] pos : u32);
-    isl_flag!(set_dim_has_upper_bound => dim_has_upper_bound, [cast(u32)] ty : DimType, [trivial] pos : u32);
-    isl_flag!(set_dim_has_any_lower_bound => dim_has_any_lower_bound, [cast(u32)] ty : DimType, [trivial] pos : u32);
-    isl_flag!(set_dim_has_any_upper_bound => dim_has_any_upper_bound, [cast(u32)] ty : DimType, [trivial] pos : u32);
-    isl_project!([into(Value)] plain_get_val_if_fixed, isl_set_plain_get_val_if_fixed, [cast(u32)] ty : DimType, [trivial] pos : u32);
+    isl_flag!(set_dim_is_bounded => dim_is_bounded, dim: Dim);
+    isl_flag!(set_dim_has_lower_bound => dim_has_lower_bound, dim: Dim);
+    isl_flag!(set_dim_has_upper_bound => dim_has_upper_bound, dim: Dim);
+    isl_flag!(set_dim_has_any_lower_bound => dim_has_any_lower_bound, dim: Dim);
+    isl_flag!(set_dim_has_any_upper_bound => dim_has_any_upper_bound, dim: Dim);
+    isl_project!([into(Value)] plain_get_val_if_fixed, isl_set_plain_get_val_if_fixed, dim: Dim);
     isl_transform!(gist, isl_set_gist, [managed] context : Set<'a>);
     isl_transform!(gist_basic_set, isl_set_gist_basic_set, [managed] context : BasicSet<'a>);
     isl_transform!(gist_params, isl_set_gist_params, [managed] context : Set<'a>);
     isl_transform!(coalesce, isl_set_coalesce);
     isl_size!(set_n_basic_set => num_basic_sets);
+    isl_project!([into(BasicSetList)] get_basic_set_list, isl_set_get_basic_set_list);
     isl_ctor!([ctx] from_str, isl_set_read_from_str, [str] str : &str);
     isl_transform!(add_constraint, isl_set_add_constraint, [managed] constraint : Constraint<'a>);
     isl_transform!([into(PiecewiseQuasiPolynomial)] cardinality, isl_set_card);
@@ -153,54 +201,49 @@ impl<'a> Set<'a> {
     isl_transform!([into(Map)] lex_gt_set, isl_set_lex_gt_set, [managed] set: Set<'a>);
     isl_transform!(insert_dims, isl_set_insert_dims, [cast(u32)] ty : DimType, [trivial] pos : u32, [trivial] num : u32);
     isl_transform!(remove_dims, isl_set_remove_dims, [cast(u32)] ty : DimType, [trivial] first : u32, [trivial] num : u32);
-    pub fn foreach_point<F>(&self, func: F) -> Result<(), crate::Error>
+    isl_transform!(project_out_param_id, isl_set_project_out_param_id, [managed] id : Ident<'a>);
+    isl_transform!(project_out_all_params, isl_set_project_out_all_params);
+    isl_transform!(lower_bound_si, isl_set_lower_bound_si, dim: Dim, [trivial] value : i32);
+    isl_transform!(upper_bound_si, isl_set_upper_bound_si, dim: Dim, [trivial] value : i32);
+    isl_transform!(preimage_pw_multi_aff, isl_set_preimage_pw_multi_aff, [managed] pma : PiecewiseMultiAffine<'a>);
+    isl_transform!([into(Map)] unwrap, isl_set_unwrap);
+    isl_transform!([into(Map)] wrapped_domain_map, isl_set_wrapped_domain_map);
+    isl_transform!([into(Map)] flatten_map, isl_set_flatten_map);
+    pub fn foreach_point<F, R>(&self, func: F) -> Result<(), crate::Error>
     where
-        F: FnMut(Point<'a>) -> Result<(), crate::Error>,
+        F: FnMut(Point<'a>) -> Result<R, crate::Error>,
+        R: IterationControl,
     {
-        struct FuncWithState<F> {
-            func: F,
-            state: Cell<Result<(), crate::Error>>,
-        }
-        let mut func = FuncWithState {
-            func,
-            state: Cell::new(Ok(())),
-        };
-        unsafe extern "C" fn callback<'a, F>(
+        unsafe extern "C" fn callback<'a, F, R>(
             point: *mut barvinok_sys::isl_point,
             user: *mut std::ffi::c_void,
         ) -> barvinok_sys::isl_stat
         where
-            F: FnMut(Point<'a>) -> Result<(), crate::Error>,
+            F: FnMut(Point<'a>) -> Result<R, crate::Error>,
+            R: IterationControl,
         {
-            let data = unsafe { &mut *(user as *mut FuncWithState<F>) };
+            let Some(point) = NonNull::new(point) else {
+                return barvinok_sys::isl_stat_isl_stat_error;
+            };
+            let state = unsafe { Callback::<F>::from_user(user) };
             let point = Point {
-                handle: NonNull::new(point).unwrap(),
+                handle: point,
                 marker: std::marker::PhantomData,
             };
-            let state = data.state.replace(Ok(()));
-            data.state.set(state.and_then(|_| (data.func)(point)));
-            if data.state.get_mut().is_ok() {
-                barvinok_sys::isl_stat_isl_stat_ok
-            } else {
-                barvinok_sys::isl_stat_isl_stat_error
-            }
+            state.run_stat(|func| func(point))
         }
-        let handle = self.handle.as_ptr();
+        let mut state = Callback::new(func);
         let res = unsafe {
             barvinok_sys::isl_set_foreach_point(
-                handle,
-                Some(callback::<F>),
-                &mut func as *mut FuncWithState<F> as *mut std::ffi::c_void,
+                self.handle.as_ptr(),
+                Some(callback::<F, R>),
+                state.as_user(),
             )
         };
-        if res == barvinok_sys::isl_stat_isl_stat_ok {
-            func.state.into_inner()
-        } else {
-            match func.state.into_inner() {
-                Ok(()) => Err(self.context_ref().last_error_or_unknown().into()),
-                Err(e) => Err(e),
-            }
-        }
+        state.finish(
+            res != barvinok_sys::isl_stat_isl_stat_ok,
+            self.context_ref(),
+        )
     }
 }
 
@@ -210,6 +253,9 @@ impl PartialEq for BasicSet<'_> {
     }
 }
 
+/// Semantic equality: both sides represent the same set of elements. Errors, such as
+/// mismatched spaces, compare unequal. [`PlainEq`](crate::plain::PlainEq) gives a cheaper
+/// structural equality with a matching hash and order.
 impl PartialEq for Set<'_> {
     fn eq(&self, other: &Self) -> bool {
         self.checked_eq(other).unwrap_or(false)
@@ -390,6 +436,209 @@ mod test {
             println!("{:?}", set);
         });
     }
+    #[test]
+    fn test_project_out_and_eliminate() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "[n] -> { [i, j] : 0 <= i < n and 0 <= j <= i }")?;
+            let projected = set.clone().project_out(DimType::Out, 1, 1)?;
+            assert_eq!(
+                projected,
+                Set::from_str(ctx, "[n] -> { [i] : 0 <= i < n }")?
+            );
+            let eliminated = set.clone().eliminate(DimType::Out, 1, 1)?;
+            assert_eq!(eliminated.num_dims()?, 2);
+            assert_eq!(
+                eliminated,
+                Set::from_str(ctx, "[n] -> { [i, j] : 0 <= i < n }")?
+            );
+            let no_params = set.clone().project_out_all_params()?;
+            assert_eq!(no_params, Set::from_str(ctx, "{ [i, j] : 0 <= j <= i }")?);
+            let n = set.get_dim_id(DimType::Param, 0)?;
+            let by_id =
+                Set::from_str(ctx, "[n] -> { [i] : 0 <= i < n }")?.project_out_param_id(n)?;
+            assert_eq!(by_id, Set::from_str(ctx, "{ [i] : i >= 0 }")?);
+
+            let bset = BasicSet::from_str(ctx, "[n] -> { [i, j] : 0 <= i < n and 0 <= j <= i }")?;
+            let projected = bset.project_out(DimType::Out, 0, 1)?;
+            assert_eq!(
+                projected,
+                BasicSet::from_str(ctx, "[n] -> { [j] : 0 <= j < n }")?
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_params() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "[n, m] -> { [i] : 0 <= i < n and m > 2 }")?;
+            let params = set.clone().params()?;
+            assert!(params.is_params()?);
+            assert_eq!(
+                params,
+                Set::from_str(ctx, "[n, m] -> { : n > 0 and m > 2 }")?
+            );
+            let lifted = params.from_params()?;
+            assert!(!lifted.is_params()?);
+            assert_eq!(lifted.num_dims()?, 0);
+
+            let unused =
+                Set::from_str(ctx, "[n, m] -> { [i] : 0 <= i < n }")?.drop_unused_params()?;
+            assert_eq!(unused.num_params()?, 1);
+
+            let model = Set::from_str(ctx, "[m, k, n] -> { : }")?.get_space()?;
+            let aligned = set.align_params(model)?;
+            assert_eq!(aligned.num_params()?, 3);
+            assert_eq!(aligned.get_dim_name(DimType::Param, 0)?, "m");
+
+            let bset = BasicSet::from_str(ctx, "[n] -> { [i] : 0 <= i < n }")?;
+            assert_eq!(bset.params()?.num_dims()?, 0);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_fix_and_bounds() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "[n] -> { [i, j] : 0 <= i, j < n }")?;
+            let fixed = set.clone().fix_si(DimType::Out, 0, 3)?;
+            assert_eq!(
+                fixed,
+                Set::from_str(ctx, "[n] -> { [3, j] : 0 <= j < n and n > 3 }")?
+            );
+            let fixed = set
+                .clone()
+                .fix_val(DimType::Param, 0, Value::new_si(ctx, 2))?;
+            assert_eq!(
+                fixed,
+                Set::from_str(ctx, "[n] -> { [i, j] : n = 2 and 0 <= i, j < 2 }")?
+            );
+            let bounded = set
+                .clone()
+                .lower_bound_si(DimType::Out, 1, 1)?
+                .upper_bound_val(DimType::Out, 1, Value::new_si(ctx, 4))?;
+            assert_eq!(
+                bounded,
+                Set::from_str(
+                    ctx,
+                    "[n] -> { [i, j] : 0 <= i < n and 1 <= j <= 4 and j < n }"
+                )?
+            );
+            let bounded = set.upper_bound_si(DimType::Out, 0, 0)?.lower_bound_val(
+                DimType::Out,
+                0,
+                Value::new_si(ctx, 0),
+            )?;
+            assert_eq!(
+                bounded,
+                Set::from_str(ctx, "[n] -> { [0, j] : 0 <= j < n }")?
+            );
+
+            let bset = BasicSet::from_str(ctx, "{ [i] : 0 <= i < 10 }")?;
+            let bset = bset.fix_si(DimType::Out, 0, 4)?.lower_bound_val(
+                DimType::Out,
+                0,
+                Value::new_si(ctx, 4),
+            )?;
+            assert_eq!(bset, BasicSet::from_str(ctx, "{ [4] }")?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_add_and_move_dims() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "{ [i] : 0 <= i < 10 }")?;
+            let set = set.add_dims(DimType::Out, 2)?;
+            assert_eq!(set.num_dims()?, 3);
+            let bset = BasicSet::from_str(ctx, "{ [i] : 0 <= i < 10 }")?
+                .add_dims(DimType::Param, 1)?
+                .insert_dims(DimType::Out, 0, 1)?
+                .move_dims(DimType::Param, 0, DimType::Out, 1, 1)?;
+            assert_eq!(bset.num_params()?, 2);
+            assert_eq!(bset.num_dims()?, 1);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_preimage_multi_aff() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "{ A[a] : 0 <= a < 10 }")?;
+            let ma = MultiAffine::from_str(ctx, "{ S[i, j] -> A[(i + j)] }")?;
+            let preimage = set.clone().preimage_multi_aff(ma.clone())?;
+            assert_eq!(
+                preimage,
+                Set::from_str(ctx, "{ S[i, j] : 0 <= i + j < 10 }")?
+            );
+            let pma = PiecewiseMultiAffine::from_str(ctx, "{ S[i, j] -> A[(i + j)] }")?;
+            assert_eq!(set.preimage_pw_multi_aff(pma)?, preimage);
+            let bset = BasicSet::from_str(ctx, "{ A[a] : 0 <= a < 10 }")?;
+            let preimage = bset.preimage_multi_aff(ma)?;
+            assert_eq!(
+                preimage,
+                BasicSet::from_str(ctx, "{ S[i, j] : 0 <= i + j < 10 }")?
+            );
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_wrapped_sets() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "{ [S[i] -> A[j]] : 0 <= i < 4 and j = 2i }")?;
+            assert!(set.is_wrapping()?);
+            let map = set.clone().unwrap()?;
+            assert_eq!(map.domain_tuple_dim()?, 1);
+            let flat = set.clone().flatten()?;
+            assert!(!flat.is_wrapping()?);
+            assert_eq!(flat.num_dims()?, 2);
+            let flatten_map = set.clone().flatten_map()?;
+            assert_eq!(flatten_map.range_tuple_dim()?, 2);
+            let domain_map = set.wrapped_domain_map()?;
+            assert_eq!(domain_map.range_tuple_dim()?, 1);
+
+            let bset = BasicSet::from_str(ctx, "{ [S[i] -> A[j]] : j = 2i }")?;
+            assert!(bset.is_wrapping()?);
+            let bmap = bset.clone().unwrap()?;
+            assert_eq!(bmap.dim(DimType::In)?, 1);
+            assert_eq!(bset.flatten()?.num_dims()?, 2);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_remove_divs() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(
+                ctx,
+                "{ [i, j] : exists k : i = 2k and 0 <= i < 10 and 0 <= j < 3 }",
+            )?;
+            let without = set.clone().remove_divs()?;
+            assert_eq!(
+                without,
+                Set::from_str(ctx, "{ [i, j] : 0 <= i < 10 and 0 <= j < 3 }")?
+            );
+            let involving = set.clone().remove_divs_involving_dims(DimType::Out, 0, 1)?;
+            assert_eq!(involving, without);
+            let kept = set.clone().remove_unknown_divs()?;
+            assert_eq!(kept, set);
+            let dropped = set.drop_constraints_involving_dims(DimType::Out, 1, 1)?;
+            assert_eq!(
+                dropped,
+                Set::from_str(ctx, "{ [i, j] : exists k : i = 2k and 0 <= i < 10 }")?
+            );
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_construct_triangular_iteration_space() -> anyhow::Result<()> {
         // for i in 0 .. n
@@ -1,5 +1,7 @@
 use crate::stat::isl_size_to_optional_u32;
-use crate::{DimType, impl_isl_handle, isl_ctor, isl_flag, isl_size, isl_str, isl_transform};
+use crate::{
+    DimType, impl_isl_handle, isl_ctor, isl_flag, isl_project, isl_size, isl_str, isl_transform,
+};
 use crate::{ident::Ident, stat::isl_bool_to_optional_bool};
 use std::mem::ManuallyDrop;
 use std::ptr::NonNull;
@@ -11,6 +13,7 @@ impl<'a> Space<'a> {
     isl_ctor!([ctx] set, isl_space_set_alloc, [trivial] num_params: u32, [trivial] num_dims: u32);
     isl_ctor!([ctx] params, isl_space_params_alloc, [trivial] num_params: u32);
     isl_ctor!([ctx] unit, isl_space_unit);
+    isl_ctor!([ctx] from_str, isl_space_read_from_str, [str] str: &str);
     isl_flag!(space_is_params => is_params);
     isl_flag!(space_is_set => is_set);
     isl_flag!(space_is_map => is_map);
@@ -23,6 +26,55 @@ impl<'a> Space<'a> {
     isl_str!(space_get_dim_name => get_dim_name, [cast(u32)] dim_type: DimType, [trivial] pos: u32);
     isl_transform!(set_dim_name, isl_space_set_dim_name, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [str] name: &str);
     isl_flag!(space_has_dim_name => has_dim_name, [cast(u32)] dim_type: DimType, [trivial] pos: u32);
+    isl_transform!(align_params, isl_space_align_params, [managed] model: Space<'a>);
+    isl_transform!(domain, isl_space_domain);
+    isl_transform!(range, isl_space_range);
+    // `params` is taken by the constructor, so the projection onto the parameters is `to_params`.
+    isl_transform!(to_params, isl_space_params);
+    isl_transform!(map_from_set, isl_space_map_from_set);
+    isl_ctor!(map_from_domain_and_range, isl_space_map_from_domain_and_range, domain: Space<'a>, [managed] range: Space<'a>);
+    isl_flag!(space_is_wrapping => is_wrapping);
+    isl_transform!(wrap, isl_space_wrap);
+    isl_transform!(unwrap, isl_space_unwrap);
+    isl_transform!(product, isl_space_product, [managed] right: Space<'a>);
+    isl_transform!(domain_product, isl_space_domain_product, [managed] right: Space<'a>);
+    isl_transform!(range_product, isl_space_range_product, [managed] right: Space<'a>);
+    isl_transform!(reverse, isl_space_reverse);
+    isl_transform!(set_dim_id, isl_space_set_dim_id, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [managed] id: Ident<'a>);
+    isl_flag!(space_has_dim_id => has_dim_id, [cast(u32)] dim_type: DimType, [trivial] pos: u32);
+    isl_project!([into(Ident)] get_dim_id, isl_space_get_dim_id, [cast(u32)] dim_type: DimType, [trivial] pos: u32);
+    isl_transform!(set_tuple_id, isl_space_set_tuple_id, [cast(u32)] dim_type: DimType, [managed] id: Ident<'a>);
+    isl_transform!(reset_tuple_id, isl_space_reset_tuple_id, [cast(u32)] dim_type: DimType);
+    isl_flag!(space_has_tuple_id => has_tuple_id, [cast(u32)] dim_type: DimType);
+    isl_project!([into(Ident)] get_tuple_id, isl_space_get_tuple_id, [cast(u32)] dim_type: DimType);
+    isl_flag!(space_is_equal => is_equal, [ref] other: &Space<'a>);
+    isl_flag!(space_has_equal_params => has_equal_params, [ref] other: &Space<'a>);
+    isl_transform!(drop_dims, isl_space_drop_dims, [cast(u32)] dim_type: DimType, [trivial] first: u32, [trivial] num: u32);
+    isl_transform!(insert_dims, isl_space_insert_dims, [cast(u32)] dim_type: DimType, [trivial] pos: u32, [trivial] num: u32);
+    isl_transform!(move_dims, isl_space_move_dims, [cast(u32)] dst_type: DimType, [trivial] dst_pos: u32, [cast(u32)] src_type: DimType, [trivial] src_pos: u32, [trivial] num: u32);
+
+    /// Position of the dimension called `name`, or `None` if there is no such dimension.
+    pub fn find_dim_by_name(
+        &self,
+        dim_type: DimType,
+        name: &str,
+    ) -> Result<Option<u32>, crate::Error> {
+        let name = std::ffi::CString::new(name)?;
+        let pos = unsafe {
+            barvinok_sys::isl_space_find_dim_by_name(
+                self.handle.as_ptr(),
+                dim_type as u32,
+                name.as_ptr(),
+            )
+        };
+        Ok(u32::try_from(pos).ok())
+    }
+}
+
+impl PartialEq for Space<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_equal(other).unwrap_or(false)
+    }
 }
 
 #[cfg(test)]
@@ -100,4 +152,86 @@ mod tests {
             assert!(space.is_map().unwrap());
         });
     }
+
+    #[test]
+    fn test_space_domain_range() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let space = Space::new(ctx, 1, 2, 3)?;
+            let domain = space.clone().domain()?;
+            let range = space.clone().range()?;
+            assert!(domain.is_set()?);
+            assert_eq!(domain.get_dim(DimType::Out)?, 2);
+            assert_eq!(range.get_dim(DimType::Out)?, 3);
+            let rebuilt = Space::map_from_domain_and_range(domain.clone(), range.clone())?;
+            assert_eq!(rebuilt, space);
+            let reversed = space.clone().reverse()?;
+            assert_eq!(reversed.get_dim(DimType::In)?, 3);
+            let params = space.clone().to_params()?;
+            assert!(params.is_params()?);
+            assert!(params.has_equal_params(&space)?);
+            let square = domain.map_from_set()?;
+            assert_eq!(square.get_dim(DimType::In)?, 2);
+            assert_eq!(square.get_dim(DimType::Out)?, 2);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_space_wrap_and_products() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let space = Space::new(ctx, 0, 1, 2)?;
+            let wrapped = space.clone().wrap()?;
+            assert!(wrapped.is_set()?);
+            assert!(wrapped.is_wrapping()?);
+            assert_eq!(wrapped.clone().unwrap()?, space);
+            let product = space.clone().product(Space::new(ctx, 0, 3, 4)?)?;
+            assert_eq!(product.get_dim(DimType::In)?, 4);
+            assert_eq!(product.get_dim(DimType::Out)?, 6);
+            let range_product = space.clone().range_product(Space::new(ctx, 0, 1, 4)?)?;
+            assert_eq!(range_product.get_dim(DimType::In)?, 1);
+            assert_eq!(range_product.get_dim(DimType::Out)?, 6);
+            let domain_product = space.domain_product(Space::new(ctx, 0, 3, 2)?)?;
+            assert_eq!(domain_product.get_dim(DimType::In)?, 4);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_space_ids() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let space = Space::set(ctx, 1, 2)?;
+            let space = space.set_dim_id(DimType::Out, 1, Ident::new(ctx, "j")?)?;
+            let space = space.set_tuple_id(DimType::Out, Ident::new(ctx, "S")?)?;
+            assert!(space.has_dim_id(DimType::Out, 1)?);
+            assert!(!space.has_dim_id(DimType::Out, 0)?);
+            assert_eq!(space.get_dim_id(DimType::Out, 1)?.name()?, "j");
+            assert!(space.has_tuple_id(DimType::Out)?);
+            assert_eq!(space.get_tuple_name(DimType::Out)?, "S");
+            assert_eq!(space.find_dim_by_name(DimType::Out, "j")?, Some(1));
+            assert_eq!(space.find_dim_by_name(DimType::Out, "k")?, None);
+            let space = space.reset_tuple_id(DimType::Out)?;
+            assert!(!space.has_tuple_id(DimType::Out)?);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_space_dims() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let space = Space::set(ctx, 1, 3)?;
+            let space = space.insert_dims(DimType::Out, 1, 2)?;
+            assert_eq!(space.get_dim(DimType::Out)?, 5);
+            let space = space.drop_dims(DimType::Out, 0, 1)?;
+            assert_eq!(space.get_dim(DimType::Out)?, 4);
+            let space = space.move_dims(DimType::Param, 1, DimType::Out, 0, 2)?;
+            assert_eq!(space.get_dim(DimType::Param)?, 3);
+            assert_eq!(space.get_dim(DimType::Out)?, 2);
+            assert_ne!(space, Space::set(ctx, 1, 2)?);
+            Ok(())
+        })
+    }
 }
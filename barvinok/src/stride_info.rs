@@ -0,0 +1,69 @@
+use std::ptr::NonNull;
+
+use crate::{aff::Affine, impl_isl_handle, isl_project, map::Map, set::Set, value::Value};
+
+impl_isl_handle!(
+    /// Stride of a single dimension: its values are of the form `offset + stride * k`.
+    [noprint] StrideInfo,
+    stride_info
+);
+
+impl<'a> StrideInfo<'a> {
+    isl_project!([into(Value)] get_stride, isl_stride_info_get_stride);
+    isl_project!([into(Affine)] get_offset, isl_stride_info_get_offset);
+}
+
+impl std::fmt::Debug for StrideInfo<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StrideInfo")
+            .field("stride", &self.get_stride().map_err(|_| std::fmt::Error)?)
+            .field("offset", &self.get_offset().map_err(|_| std::fmt::Error)?)
+            .finish()
+    }
+}
+
+impl<'a> Set<'a> {
+    isl_project!([into(StrideInfo)] get_stride_info, isl_set_get_stride_info, [cast(i32)] pos: u32);
+    isl_project!([into(Value)] get_stride, isl_set_get_stride, [cast(i32)] pos: u32);
+}
+
+impl<'a> Map<'a> {
+    isl_project!([into(StrideInfo)] get_range_stride_info, isl_map_get_range_stride_info, [cast(i32)] pos: u32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+
+    #[test]
+    fn test_set_stride_info() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(
+                ctx,
+                "{ [i, j] : exists k : i = 3k + 1 and 0 <= i < 30 and 0 <= j < 4 }",
+            )?;
+            let info = set.get_stride_info(0)?;
+            assert_eq!(info.get_stride()?.numerator(), 3);
+            assert_eq!(info.get_offset()?.get_constant_val()?.numerator(), 1);
+            assert_eq!(set.get_stride(1)?.numerator(), 1);
+            println!("{:?}", info);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_map_range_stride_info() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let access = Map::from_str(
+                ctx,
+                "{ S[i] -> A[o] : exists j : o = 4j + 2 and 0 <= j < i }",
+            )?;
+            let info = access.get_range_stride_info(0)?;
+            assert_eq!(info.get_stride()?.numerator(), 4);
+            Ok(())
+        })
+    }
+}
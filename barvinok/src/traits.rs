@@ -0,0 +1,383 @@
+//! Traits shared by the ISL object types, for code that is generic over sets, maps,
+//! affine expressions, polynomials and spaces.
+use std::{ffi::CString, mem::ManuallyDrop, ptr::NonNull};
+
+use crate::{
+    ContextRef, DimType,
+    aff::{Affine, MultiAffine, MultiUnionPiecewiseAffine, PiecewiseAffine, PiecewiseMultiAffine},
+    constraint::Constraint,
+    dim::SelectDim,
+    fixed_box::FixedBox,
+    ident::Ident,
+    local_space::LocalSpace,
+    map::{BasicMap, Map},
+    point::Point,
+    polynomial::{PiecewiseQuasiPolynomial, QuasiPolynomial},
+    set::{BasicSet, Set},
+    space::Space,
+    union_map::UnionMap,
+    union_set::UnionSet,
+    value::MultiValue,
+};
+
+/// Any ISL object owned by a context.
+pub trait IslObject<'a> {
+    fn context_ref(&self) -> ContextRef<'a>;
+}
+
+/// ISL objects that ISL knows how to print.
+pub trait IslPrintable<'a>: IslObject<'a> {
+    /// Print the object to stderr.
+    fn dump(&self);
+
+    /// The textual ISL representation of the object, as printed by `{:?}`.
+    fn to_isl_string(&self) -> String;
+}
+
+/// ISL objects living in a [`Space`].
+///
+/// The provided methods query the space, so they behave the same for every object.
+pub trait HasSpace<'a>: IslObject<'a> {
+    fn get_space(&self) -> Result<Space<'a>, crate::Error>;
+
+    fn dim(&self, dim_type: DimType) -> Result<u32, crate::Error> {
+        self.get_space()?.get_dim(dim_type)
+    }
+
+    /// Name of the dimension, or `None` if it is anonymous.
+    fn dim_name(&self, dim_type: DimType, pos: u32) -> Result<Option<String>, crate::Error> {
+        let space = self.get_space()?;
+        if !space.has_dim_name(dim_type, pos)? {
+            return Ok(None);
+        }
+        Ok(Some(space.get_dim_name(dim_type, pos)?.to_owned()))
+    }
+
+    /// Like [`Self::dim_name`], but selects the dimension with a [`Dim`](crate::dim::Dim) or
+    /// any other [`SelectDim`].
+    fn dim_name_by(&self, dim: impl SelectDim) -> Result<Option<String>, crate::Error> {
+        let (dim_type, pos) = dim.resolve_in(self)?;
+        self.dim_name(dim_type, pos)
+    }
+
+    /// Names of all the dimensions of the given type.
+    fn dim_names(&self, dim_type: DimType) -> Result<Vec<Option<String>>, crate::Error> {
+        (0..self.dim(dim_type)?)
+            .map(|pos| self.dim_name(dim_type, pos))
+            .collect()
+    }
+
+    /// Position of the dimension called `name`, or `None` if there is no such dimension.
+    fn find_dim_by_name(&self, dim_type: DimType, name: &str) -> Result<Option<u32>, crate::Error> {
+        self.get_space()?.find_dim_by_name(dim_type, name)
+    }
+
+    fn dim_id(&self, dim_type: DimType, pos: u32) -> Result<Option<Ident<'a>>, crate::Error> {
+        let space = self.get_space()?;
+        if !space.has_dim_id(dim_type, pos)? {
+            return Ok(None);
+        }
+        space.get_dim_id(dim_type, pos).map(Some)
+    }
+
+    /// Like [`Self::dim_id`], but selects the dimension with a [`Dim`](crate::dim::Dim) or
+    /// any other [`SelectDim`].
+    fn dim_id_by(&self, dim: impl SelectDim) -> Result<Option<Ident<'a>>, crate::Error> {
+        let (dim_type, pos) = dim.resolve_in(self)?;
+        self.dim_id(dim_type, pos)
+    }
+}
+
+/// ISL objects whose dimensions can be renamed.
+pub trait DimOps<'a>: HasSpace<'a> + Sized {
+    fn set_dim_name(self, dim_type: DimType, pos: u32, name: &str) -> Result<Self, crate::Error>;
+
+    /// Like [`Self::set_dim_name`], but selects the dimension with a [`Dim`](crate::dim::Dim)
+    /// or any other [`SelectDim`].
+    fn set_dim_name_by(self, dim: impl SelectDim, name: &str) -> Result<Self, crate::Error> {
+        let (dim_type, pos) = dim.resolve_in(&self)?;
+        self.set_dim_name(dim_type, pos, name)
+    }
+
+    /// Rename every named dimension of the given type with `rename`.
+    /// Anonymous dimensions are left untouched.
+    fn rename_dims<F>(self, dim_type: DimType, mut rename: F) -> Result<Self, crate::Error>
+    where
+        F: FnMut(&str) -> String,
+    {
+        let names = self.dim_names(dim_type)?;
+        names
+            .into_iter()
+            .zip(0..)
+            .try_fold(self, |this, (name, pos)| match name {
+                Some(name) => this.set_dim_name(dim_type, pos, &rename(&name)),
+                None => Ok(this),
+            })
+    }
+
+    /// Rename every named parameter with `rename`.
+    fn rename_params<F>(self, rename: F) -> Result<Self, crate::Error>
+    where
+        F: FnMut(&str) -> String,
+    {
+        self.rename_dims(DimType::Param, rename)
+    }
+}
+
+/// ISL objects whose tuples can be identified.
+pub trait TupleIds<'a>: HasSpace<'a> + Sized {
+    /// Sets only have the [`DimType::Out`] tuple; other dimension types are rejected with
+    /// [`Error::InvalidDimType`](crate::Error::InvalidDimType).
+    fn set_tuple_id(self, dim_type: DimType, id: Ident<'a>) -> Result<Self, crate::Error>;
+
+    fn set_tuple_name(self, dim_type: DimType, name: &str) -> Result<Self, crate::Error> {
+        let id = Ident::new(self.context_ref(), name)?;
+        self.set_tuple_id(dim_type, id)
+    }
+
+    fn tuple_id(&self, dim_type: DimType) -> Result<Option<Ident<'a>>, crate::Error> {
+        let space = self.get_space()?;
+        if !space.has_tuple_id(dim_type)? {
+            return Ok(None);
+        }
+        space.get_tuple_id(dim_type).map(Some)
+    }
+
+    fn tuple_name(&self, dim_type: DimType) -> Result<Option<String>, crate::Error> {
+        let space = self.get_space()?;
+        if !space.has_tuple_name(dim_type)? {
+            return Ok(None);
+        }
+        Ok(Some(space.get_tuple_name(dim_type)?.to_owned()))
+    }
+}
+
+macro_rules! wrap_result {
+    ($type:ident, $ctx:ident, $raw:expr) => {{
+        let handle = NonNull::new($raw).ok_or_else(|| $ctx.last_error_or_unknown())?;
+        Ok($type {
+            handle,
+            marker: std::marker::PhantomData,
+        })
+    }};
+}
+
+macro_rules! impl_has_space {
+    ($type:ident, $get_space:ident) => {
+        impl<'a> HasSpace<'a> for $type<'a> {
+            fn get_space(&self) -> Result<Space<'a>, crate::Error> {
+                let ctx = self.context_ref();
+                wrap_result!(Space, ctx, unsafe {
+                    barvinok_sys::$get_space(self.handle.as_ptr())
+                })
+            }
+        }
+    };
+}
+
+macro_rules! impl_dim_ops {
+    ($type:ident, $set_dim_name:ident) => {
+        impl<'a> DimOps<'a> for $type<'a> {
+            fn set_dim_name(
+                self,
+                dim_type: DimType,
+                pos: u32,
+                name: &str,
+            ) -> Result<Self, crate::Error> {
+                let ctx = self.context_ref();
+                let name = CString::new(name)?;
+                let this = ManuallyDrop::new(self);
+                wrap_result!($type, ctx, unsafe {
+                    barvinok_sys::$set_dim_name(
+                        this.handle.as_ptr(),
+                        dim_type as u32,
+                        pos,
+                        name.as_ptr(),
+                    )
+                })
+            }
+        }
+    };
+    // ISL only exports `set_dim_id` for some piecewise types.
+    ([id] $type:ident, $set_dim_id:ident) => {
+        impl<'a> DimOps<'a> for $type<'a> {
+            fn set_dim_name(
+                self,
+                dim_type: DimType,
+                pos: u32,
+                name: &str,
+            ) -> Result<Self, crate::Error> {
+                let ctx = self.context_ref();
+                let id = ManuallyDrop::new(Ident::new(ctx, name)?);
+                let this = ManuallyDrop::new(self);
+                wrap_result!($type, ctx, unsafe {
+                    barvinok_sys::$set_dim_id(
+                        this.handle.as_ptr(),
+                        dim_type as u32,
+                        pos,
+                        id.handle.as_ptr(),
+                    )
+                })
+            }
+        }
+    };
+}
+
+macro_rules! impl_tuple_ids {
+    ($type:ident, $set_tuple_id:ident) => {
+        impl<'a> TupleIds<'a> for $type<'a> {
+            fn set_tuple_id(self, dim_type: DimType, id: Ident<'a>) -> Result<Self, crate::Error> {
+                let ctx = self.context_ref();
+                let id = ManuallyDrop::new(id);
+                let this = ManuallyDrop::new(self);
+                wrap_result!($type, ctx, unsafe {
+                    barvinok_sys::$set_tuple_id(
+                        this.handle.as_ptr(),
+                        dim_type as u32,
+                        id.handle.as_ptr(),
+                    )
+                })
+            }
+        }
+    };
+    ([set] $type:ident, $set_tuple_id:ident) => {
+        impl<'a> TupleIds<'a> for $type<'a> {
+            fn set_tuple_id(self, dim_type: DimType, id: Ident<'a>) -> Result<Self, crate::Error> {
+                if !matches!(dim_type, DimType::Out) {
+                    return Err(crate::Error::InvalidDimType);
+                }
+                let ctx = self.context_ref();
+                let id = ManuallyDrop::new(id);
+                let this = ManuallyDrop::new(self);
+                wrap_result!($type, ctx, unsafe {
+                    barvinok_sys::$set_tuple_id(this.handle.as_ptr(), id.handle.as_ptr())
+                })
+            }
+        }
+    };
+}
+
+impl_has_space!(Set, isl_set_get_space);
+impl_has_space!(BasicSet, isl_basic_set_get_space);
+impl_has_space!(Map, isl_map_get_space);
+impl_has_space!(BasicMap, isl_basic_map_get_space);
+impl_has_space!(UnionSet, isl_union_set_get_space);
+impl_has_space!(UnionMap, isl_union_map_get_space);
+impl_has_space!(Affine, isl_aff_get_space);
+impl_has_space!(PiecewiseAffine, isl_pw_aff_get_space);
+impl_has_space!(MultiAffine, isl_multi_aff_get_space);
+impl_has_space!(PiecewiseMultiAffine, isl_pw_multi_aff_get_space);
+impl_has_space!(MultiUnionPiecewiseAffine, isl_multi_union_pw_aff_get_space);
+impl_has_space!(QuasiPolynomial, isl_qpolynomial_get_space);
+impl_has_space!(PiecewiseQuasiPolynomial, isl_pw_qpolynomial_get_space);
+impl_has_space!(LocalSpace, isl_local_space_get_space);
+impl_has_space!(Constraint, isl_constraint_get_space);
+impl_has_space!(Point, isl_point_get_space);
+impl_has_space!(MultiValue, isl_multi_val_get_space);
+impl_has_space!(FixedBox, isl_fixed_box_get_space);
+impl_has_space!(Space, isl_space_copy);
+
+impl_dim_ops!(Set, isl_set_set_dim_name);
+impl_dim_ops!(BasicSet, isl_basic_set_set_dim_name);
+impl_dim_ops!(Map, isl_map_set_dim_name);
+impl_dim_ops!(BasicMap, isl_basic_map_set_dim_name);
+impl_dim_ops!(Affine, isl_aff_set_dim_name);
+impl_dim_ops!([id] PiecewiseAffine, isl_pw_aff_set_dim_id);
+impl_dim_ops!(MultiAffine, isl_multi_aff_set_dim_name);
+impl_dim_ops!([id] PiecewiseMultiAffine, isl_pw_multi_aff_set_dim_id);
+impl_dim_ops!(
+    MultiUnionPiecewiseAffine,
+    isl_multi_union_pw_aff_set_dim_name
+);
+impl_dim_ops!(QuasiPolynomial, isl_qpolynomial_set_dim_name);
+impl_dim_ops!(PiecewiseQuasiPolynomial, isl_pw_qpolynomial_set_dim_name);
+impl_dim_ops!(LocalSpace, isl_local_space_set_dim_name);
+impl_dim_ops!(MultiValue, isl_multi_val_set_dim_name);
+impl_dim_ops!(Space, isl_space_set_dim_name);
+
+impl_tuple_ids!([set] Set, isl_set_set_tuple_id);
+impl_tuple_ids!([set] BasicSet, isl_basic_set_set_tuple_id);
+impl_tuple_ids!(Map, isl_map_set_tuple_id);
+impl_tuple_ids!(BasicMap, isl_basic_map_set_tuple_id);
+impl_tuple_ids!(Affine, isl_aff_set_tuple_id);
+impl_tuple_ids!(PiecewiseAffine, isl_pw_aff_set_tuple_id);
+impl_tuple_ids!(MultiAffine, isl_multi_aff_set_tuple_id);
+impl_tuple_ids!(PiecewiseMultiAffine, isl_pw_multi_aff_set_tuple_id);
+impl_tuple_ids!(
+    MultiUnionPiecewiseAffine,
+    isl_multi_union_pw_aff_set_tuple_id
+);
+impl_tuple_ids!(LocalSpace, isl_local_space_set_tuple_id);
+impl_tuple_ids!(MultiValue, isl_multi_val_set_tuple_id);
+impl_tuple_ids!(Space, isl_space_set_tuple_id);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+
+    fn prefix_params<'a, T: DimOps<'a>>(obj: T) -> Result<T, crate::Error> {
+        obj.rename_params(|name| format!("p_{name}"))
+    }
+
+    #[test]
+    fn test_generic_rename() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = prefix_params(Set::from_str(ctx, "[n, m] -> { [i] : 0 <= i < n + m }")?)?;
+            assert_eq!(
+                set.dim_names(DimType::Param)?,
+                [Some("p_n".into()), Some("p_m".into())]
+            );
+            assert_eq!(
+                HasSpace::find_dim_by_name(&set, DimType::Param, "p_m")?,
+                Some(1)
+            );
+            assert_eq!(HasSpace::find_dim_by_name(&set, DimType::Param, "m")?, None);
+
+            let pa = prefix_params(PiecewiseAffine::from_str(ctx, "[n] -> { [i] -> [(n)] }")?)?;
+            assert_eq!(pa.dim_name(DimType::Param, 0)?.as_deref(), Some("p_n"));
+
+            let qp = QuasiPolynomial::var_on_domain(set.get_space()?, DimType::Param, 0)?;
+            let qp = DimOps::set_dim_name(qp, DimType::In, 0, "i")?;
+            assert_eq!(qp.dim_name(DimType::In, 0)?.as_deref(), Some("i"));
+            assert_eq!(qp.dim(DimType::Param)?, 2);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_tuple_ids() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "{ [i] : 0 <= i < 10 }")?;
+            assert_eq!(set.tuple_name(DimType::Out)?, None);
+            let set = TupleIds::set_tuple_name(set, DimType::Out, "S")?;
+            assert_eq!(set.tuple_name(DimType::Out)?.as_deref(), Some("S"));
+            let err = TupleIds::set_tuple_name(set, DimType::In, "T").unwrap_err();
+            assert!(matches!(err, crate::Error::InvalidDimType));
+
+            let map = Map::from_str(ctx, "{ [i] -> [j] }")?;
+            let map = TupleIds::set_tuple_name(map, DimType::In, "A")?;
+            let map = TupleIds::set_tuple_name(map, DimType::Out, "B")?;
+            assert_eq!(map.tuple_id(DimType::In)?.unwrap().name()?, "A");
+            assert_eq!(map.tuple_name(DimType::Out)?.as_deref(), Some("B"));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_object_basics() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let set = Set::from_str(ctx, "{ [i] : 0 <= i < 10 }")?;
+            let object: &dyn IslPrintable = &set;
+            assert_eq!(object.to_isl_string(), format!("{set:?}"));
+            assert_eq!(
+                object.context_ref().0.as_ptr(),
+                IslObject::context_ref(&set.get_space()?).0.as_ptr()
+            );
+            Ok(())
+        })
+    }
+}
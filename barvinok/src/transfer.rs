@@ -0,0 +1,195 @@
+//! Moving ISL objects between contexts.
+//!
+//! A [`Context`](crate::Context) is single-threaded, so parallel workers each own one and
+//! exchange objects by transferring them. A transfer prints the object in its own context
+//! and parses the text in the target context. Tuple and dimension names survive the round
+//! trip. User data attached to identifiers inside an object cannot be copied in general and
+//! is lost; [`Ident::transfer_with_user`] copies it for a single identifier whose user data
+//! type is known.
+
+use crate::{
+    ContextRef, Error,
+    aff::{Affine, MultiAffine, MultiUnionPiecewiseAffine, PiecewiseAffine, PiecewiseMultiAffine},
+    fixed_box::FixedBox,
+    ident::Ident,
+    map::{BasicMap, Map},
+    polynomial::{PiecewiseQuasiPolynomial, QuasiPolynomial},
+    schedule::{Schedule, ScheduleConstraints},
+    set::{BasicSet, Set},
+    space::Space,
+    traits::IslPrintable,
+    union_map::UnionMap,
+    union_set::UnionSet,
+    value::{MultiValue, Value},
+};
+
+/// ISL objects that can be copied into another context.
+pub trait Transfer<'a>: IslPrintable<'a> {
+    /// The same type, owned by the target context.
+    type Target<'b>;
+
+    /// Copy the object into `ctx`. The object itself stays in its own context.
+    fn transfer_to<'b>(&self, ctx: ContextRef<'b>) -> Result<Self::Target<'b>, Error>;
+}
+
+macro_rules! impl_transfer {
+    ($($type:ident),* $(,)?) => {
+        $(
+            impl<'a> Transfer<'a> for $type<'a> {
+                type Target<'b> = $type<'b>;
+
+                fn transfer_to<'b>(&self, ctx: ContextRef<'b>) -> Result<$type<'b>, Error> {
+                    $type::from_str(ctx, &self.to_isl_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_transfer!(
+    Set,
+    BasicSet,
+    Map,
+    BasicMap,
+    UnionSet,
+    UnionMap,
+    Affine,
+    MultiAffine,
+    PiecewiseAffine,
+    PiecewiseMultiAffine,
+    MultiUnionPiecewiseAffine,
+    PiecewiseQuasiPolynomial,
+    MultiValue,
+    Space,
+    FixedBox,
+    Schedule,
+    ScheduleConstraints,
+);
+
+impl<'a> Transfer<'a> for Value<'a> {
+    type Target<'b> = Value<'b>;
+
+    fn transfer_to<'b>(&self, ctx: ContextRef<'b>) -> Result<Value<'b>, Error> {
+        Value::new_from_string(ctx, &self.to_isl_string())
+    }
+}
+
+impl<'a> Transfer<'a> for QuasiPolynomial<'a> {
+    type Target<'b> = QuasiPolynomial<'b>;
+
+    fn transfer_to<'b>(&self, ctx: ContextRef<'b>) -> Result<QuasiPolynomial<'b>, Error> {
+        // ISL cannot parse a bare quasi-polynomial, so go through a single piece.
+        PiecewiseQuasiPolynomial::try_from(self.clone())?
+            .transfer_to(ctx)?
+            .as_qpolynomial()
+    }
+}
+
+/// Transfers the name only, see [`Ident::transfer_with_user`] to keep the user data.
+impl<'a> Transfer<'a> for Ident<'a> {
+    type Target<'b> = Ident<'b>;
+
+    fn transfer_to<'b>(&self, ctx: ContextRef<'b>) -> Result<Ident<'b>, Error> {
+        Ident::new(ctx, self.name()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, DimType, dim::Dim};
+
+    #[test]
+    fn test_sets_and_maps_round_trip() -> anyhow::Result<()> {
+        let source = Context::new();
+        let target = Context::new();
+        source.scope(|src| {
+            target.scope(|dst| {
+                let set = Set::from_str(src, "[n] -> { A[i, j] : 0 <= i < n and 0 <= j < i }")?;
+                let moved = set.transfer_to(dst)?;
+                assert!(moved.context_ref() == dst);
+                assert_eq!(moved.get_tuple_name()?, "A");
+                assert_eq!(moved.get_dim_name(DimType::Param, 0)?, "n");
+                assert_eq!(moved.transfer_to(src)?, set);
+
+                let map = Map::from_str(src, "[n] -> { A[i] -> B[i + n] }")?;
+                assert_eq!(map.transfer_to(dst)?.transfer_to(src)?, map);
+
+                let space = set.get_space()?;
+                assert_eq!(space.transfer_to(dst)?.transfer_to(src)?, space);
+
+                let value = Value::new_from_string(src, "7/3")?;
+                assert_eq!(value.transfer_to(dst)?.transfer_to(src)?, value);
+                Ok(())
+            })
+        })
+    }
+
+    #[test]
+    fn test_union_types_round_trip() -> anyhow::Result<()> {
+        let source = Context::new();
+        let target = Context::new();
+        source.scope(|src| {
+            target.scope(|dst| {
+                let uset = UnionSet::from_str(
+                    src,
+                    "[n] -> { A[i] : 0 <= i < n; B[i, j] : 0 <= i, j < n }",
+                )?;
+                assert_eq!(uset.transfer_to(dst)?.transfer_to(src)?, uset);
+
+                let umap = UnionMap::from_str(src, "{ A[i] -> B[i, i]; B[i, j] -> A[j] }")?;
+                assert_eq!(umap.transfer_to(dst)?.transfer_to(src)?, umap);
+                Ok(())
+            })
+        })
+    }
+
+    #[test]
+    fn test_polynomials_round_trip() -> anyhow::Result<()> {
+        let source = Context::new();
+        let target = Context::new();
+        source.scope(|src| {
+            target.scope(|dst| {
+                let pwqp = PiecewiseQuasiPolynomial::from_str(
+                    src,
+                    "[n] -> { [i] -> i * n + floor(i / 2) : 0 <= i < n; [i] -> 1 : i >= n }",
+                )?;
+                let back = pwqp.transfer_to(dst)?.transfer_to(src)?;
+                assert!((back - pwqp)?.is_zero()?);
+
+                let space = Space::set(src, 1, 1)?.set_dim_name(DimType::Param, 0, "n")?;
+                let n = QuasiPolynomial::var_on_domain_by(space.clone(), Dim::param("n"))?;
+                let i = QuasiPolynomial::var_on_domain_by(space, Dim::out(0))?;
+                let qp = (n * i)?;
+                let moved = qp.transfer_to(dst)?;
+                assert!(moved.context_ref() == dst);
+                assert_eq!(moved.to_isl_string(), qp.to_isl_string());
+                Ok(())
+            })
+        })
+    }
+
+    #[test]
+    fn test_ident_user_data() -> anyhow::Result<()> {
+        let source = Context::new();
+        let target = Context::new();
+        source.scope(|src| {
+            target.scope(|dst| {
+                let id = Ident::new_with_user(src, "x", String::from("payload"))?;
+                let plain = id.transfer_to(dst)?;
+                assert_eq!(plain.name()?, "x");
+                assert!(plain.get_user_ref().is_none());
+
+                let with_user = id.transfer_with_user::<String>(dst)?;
+                assert_eq!(with_user.name()?, "x");
+                assert_eq!(
+                    with_user.get_user_as::<String>().map(String::as_str),
+                    Some("payload")
+                );
+                // user data of another type is not copied
+                assert!(id.transfer_with_user::<i32>(dst)?.get_user_ref().is_none());
+                Ok(())
+            })
+        })
+    }
+}
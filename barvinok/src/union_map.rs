@@ -0,0 +1,168 @@
+use std::{mem::ManuallyDrop, ptr::NonNull};
+
+use crate::{
+    impl_isl_handle, isl_ctor, isl_flag, isl_project, isl_size, isl_transform, isl_transform_exact,
+    map::{BasicMap, Map},
+    set::Set,
+    space::Space,
+    stat::{isl_bool_to_optional_bool, isl_size_to_optional_u32},
+    union_set::UnionSet,
+    value::Value,
+};
+
+impl_isl_handle!(UnionMap, union_map);
+
+#[allow(clippy::should_implement_trait)]
+impl<'a> UnionMap<'a> {
+    isl_ctor!([ctx] empty_ctx, isl_union_map_empty_ctx);
+    isl_ctor!(empty, isl_union_map_empty_space, space : Space<'a>);
+    isl_ctor!([ctx] from_str, isl_union_map_read_from_str, [str] str : &str);
+    isl_ctor!(from_domain_and_range, isl_union_map_from_domain_and_range, domain : UnionSet<'a>, [managed] range : UnionSet<'a>);
+    isl_ctor!(from_domain, isl_union_map_from_domain, domain : UnionSet<'a>);
+    isl_ctor!(from_range, isl_union_map_from_range, range : UnionSet<'a>);
+    isl_project!([into(Space)] get_space, isl_union_map_get_space);
+    isl_size!(union_map_n_map => num_maps);
+    isl_project!([into(Map)] extract_map, isl_union_map_extract_map, [managed] space : Space<'a>);
+    isl_flag!(union_map_isa_map => is_map);
+    isl_transform!([into(Map)] as_map, isl_union_map_as_map);
+    isl_transform!([into(Set)] params, isl_union_map_params);
+    isl_transform!([into(UnionSet)] domain, isl_union_map_domain);
+    isl_transform!([into(UnionSet)] range, isl_union_map_range);
+    isl_transform!([into(UnionSet)] deltas, isl_union_map_deltas);
+    isl_transform!([into(UnionSet)] wrap, isl_union_map_wrap);
+    isl_transform!(domain_map, isl_union_map_domain_map);
+    isl_transform!(range_map, isl_union_map_range_map);
+    isl_transform!(universe, isl_union_map_universe);
+    isl_transform!(reverse, isl_union_map_reverse);
+    isl_transform!(add_map, isl_union_map_add_map, [managed] map : Map<'a>);
+    isl_transform!(union, isl_union_map_union, [managed] other : UnionMap<'a>);
+    isl_transform!(intersect, isl_union_map_intersect, [managed] other : UnionMap<'a>);
+    isl_transform!(intersect_params, isl_union_map_intersect_params, [managed] set : Set<'a>);
+    isl_transform!(intersect_domain, isl_union_map_intersect_domain_union_set, [managed] domain : UnionSet<'a>);
+    isl_transform!(intersect_range, isl_union_map_intersect_range_union_set, [managed] range : UnionSet<'a>);
+    isl_transform!(subtract, isl_union_map_subtract, [managed] other : UnionMap<'a>);
+    isl_transform!(subtract_domain, isl_union_map_subtract_domain, [managed] domain : UnionSet<'a>);
+    isl_transform!(subtract_range, isl_union_map_subtract_range, [managed] range : UnionSet<'a>);
+    isl_transform!(apply_domain, isl_union_map_apply_domain, [managed] other : UnionMap<'a>);
+    isl_transform!(apply_range, isl_union_map_apply_range, [managed] other : UnionMap<'a>);
+    isl_transform!(product, isl_union_map_product, [managed] other : UnionMap<'a>);
+    isl_transform!(domain_product, isl_union_map_domain_product, [managed] other : UnionMap<'a>);
+    isl_transform!(range_product, isl_union_map_range_product, [managed] other : UnionMap<'a>);
+    isl_transform!(gist, isl_union_map_gist, [managed] context : UnionMap<'a>);
+    isl_transform!(gist_params, isl_union_map_gist_params, [managed] set : Set<'a>);
+    isl_transform!(gist_domain, isl_union_map_gist_domain, [managed] domain : UnionSet<'a>);
+    isl_transform!(gist_range, isl_union_map_gist_range, [managed] range : UnionSet<'a>);
+    isl_transform!(fixed_power_val, isl_union_map_fixed_power_val, [managed] exp : Value<'a>);
+    isl_transform_exact!(transitive_closure, isl_union_map_transitive_closure);
+    isl_transform_exact!(power, isl_union_map_power);
+    isl_transform!(coalesce, isl_union_map_coalesce);
+    isl_transform!(detect_equalities, isl_union_map_detect_equalities);
+    isl_transform!(compute_divs, isl_union_map_compute_divs);
+    isl_transform!(lexmin, isl_union_map_lexmin);
+    isl_transform!(lexmax, isl_union_map_lexmax);
+    isl_transform!(curry, isl_union_map_curry);
+    isl_transform!(uncurry, isl_union_map_uncurry);
+    isl_transform!(zip, isl_union_map_zip);
+    isl_transform!(lex_lt_union_map, isl_union_map_lex_lt_union_map, [managed] other : UnionMap<'a>);
+    isl_transform!(lex_le_union_map, isl_union_map_lex_le_union_map, [managed] other : UnionMap<'a>);
+    isl_transform!(lex_gt_union_map, isl_union_map_lex_gt_union_map, [managed] other : UnionMap<'a>);
+    isl_transform!(lex_ge_union_map, isl_union_map_lex_ge_union_map, [managed] other : UnionMap<'a>);
+    isl_flag!(union_map_is_empty => is_empty);
+    isl_flag!(union_map_is_single_valued => is_single_valued);
+    isl_flag!(union_map_is_injective => is_injective);
+    isl_flag!(union_map_is_bijective => is_bijective);
+    isl_flag!(union_map_is_identity => is_identity);
+    isl_flag!(union_map_is_equal => checked_eq, [ref] other : &UnionMap<'a>);
+    isl_flag!(union_map_is_disjoint => disjoint, [ref] other : &UnionMap<'a>);
+    isl_flag!(union_map_is_subset => subset, [ref] other : &UnionMap<'a>);
+    isl_flag!(union_map_is_strict_subset => strict_subset, [ref] other : &UnionMap<'a>);
+}
+
+impl PartialEq for UnionMap<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.checked_eq(other).unwrap_or(false)
+    }
+}
+
+impl<'a> TryFrom<Map<'a>> for UnionMap<'a> {
+    fn try_from(map: Map<'a>) -> Result<Self, crate::Error> {
+        let ctx = map.context_ref();
+        let map = ManuallyDrop::new(map);
+        let handle = unsafe { barvinok_sys::isl_union_map_from_map(map.handle.as_ptr()) };
+        let handle = NonNull::new(handle).ok_or_else(|| ctx.last_error_or_unknown())?;
+        Ok(UnionMap {
+            handle,
+            marker: std::marker::PhantomData,
+        })
+    }
+
+    type Error = crate::Error;
+}
+
+impl<'a> TryFrom<BasicMap<'a>> for UnionMap<'a> {
+    fn try_from(basic_map: BasicMap<'a>) -> Result<Self, crate::Error> {
+        let ctx = basic_map.context_ref();
+        let basic_map = ManuallyDrop::new(basic_map);
+        let handle =
+            unsafe { barvinok_sys::isl_union_map_from_basic_map(basic_map.handle.as_ptr()) };
+        let handle = NonNull::new(handle).ok_or_else(|| ctx.last_error_or_unknown())?;
+        Ok(UnionMap {
+            handle,
+            marker: std::marker::PhantomData,
+        })
+    }
+
+    type Error = crate::Error;
+}
+
+impl<'a> UnionSet<'a> {
+    isl_transform!([into(UnionMap)] unwrap, isl_union_set_unwrap);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+
+    #[test]
+    fn test_union_map_from_str() {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let umap = UnionMap::from_str(
+                ctx,
+                "[n] -> { S[i] -> A[i] : 0 <= i < n; T[i] -> A[i + 1] : 0 <= i < n }",
+            )
+            .unwrap();
+            assert_eq!(umap.num_maps().unwrap(), 2);
+            let range = umap.clone().range().unwrap();
+            assert_eq!(range.num_sets().unwrap(), 1);
+            println!("{:?}", umap);
+        });
+    }
+
+    #[test]
+    fn test_union_map_apply() {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let write = UnionMap::from_str(ctx, "{ S[i] -> A[i] : 0 <= i < 10 }").unwrap();
+            let read = UnionMap::from_str(ctx, "{ T[i] -> A[i] : 0 <= i < 10 }").unwrap();
+            let dep = write.apply_range(read.reverse().unwrap()).unwrap();
+            let expected = UnionMap::from_str(ctx, "{ S[i] -> T[i] : 0 <= i < 10 }").unwrap();
+            assert_eq!(dep, expected);
+            assert!(dep.is_single_valued().unwrap());
+            assert!(dep.is_injective().unwrap());
+        });
+    }
+
+    #[test]
+    fn test_union_map_transitive_closure() {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let dep = UnionMap::from_str(ctx, "{ S[i] -> S[i + 1] : 0 <= i < 9 }").unwrap();
+            let (closure, exact) = dep.transitive_closure().unwrap();
+            assert!(exact);
+            let expected = UnionMap::from_str(ctx, "{ S[i] -> S[j] : 0 <= i < j <= 9 }").unwrap();
+            assert_eq!(closure, expected);
+        });
+    }
+}
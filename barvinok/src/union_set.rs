@@ -0,0 +1,144 @@
+use std::{mem::ManuallyDrop, ptr::NonNull};
+
+use crate::{
+    impl_isl_handle, isl_ctor, isl_flag, isl_project, isl_size, isl_transform,
+    list::List,
+    set::{BasicSet, Set},
+    space::Space,
+    stat::{isl_bool_to_optional_bool, isl_size_to_optional_u32},
+    union_map::UnionMap,
+};
+
+impl_isl_handle!(UnionSet, union_set);
+
+type SetList<'a> = List<'a, Set<'a>>;
+
+#[allow(clippy::should_implement_trait)]
+impl<'a> UnionSet<'a> {
+    isl_ctor!([ctx] empty_ctx, isl_union_set_empty_ctx);
+    isl_ctor!(empty, isl_union_set_empty_space, space : Space<'a>);
+    isl_ctor!([ctx] from_str, isl_union_set_read_from_str, [str] str : &str);
+    isl_project!([into(Space)] get_space, isl_union_set_get_space);
+    isl_size!(union_set_n_set => num_sets);
+    isl_project!([into(SetList)] get_set_list, isl_union_set_get_set_list);
+    isl_project!([into(Set)] extract_set, isl_union_set_extract_set, [managed] space : Space<'a>);
+    isl_flag!(union_set_isa_set => is_set);
+    isl_transform!([into(Set)] as_set, isl_union_set_as_set);
+    isl_transform!([into(Set)] params, isl_union_set_params);
+    isl_transform!(universe, isl_union_set_universe);
+    isl_transform!(add_set, isl_union_set_add_set, [managed] set : Set<'a>);
+    isl_transform!(union, isl_union_set_union, [managed] other : UnionSet<'a>);
+    isl_transform!(intersect, isl_union_set_intersect, [managed] other : UnionSet<'a>);
+    isl_transform!(intersect_params, isl_union_set_intersect_params, [managed] set : Set<'a>);
+    isl_transform!(subtract, isl_union_set_subtract, [managed] other : UnionSet<'a>);
+    isl_transform!(product, isl_union_set_product, [managed] other : UnionSet<'a>);
+    isl_transform!(gist, isl_union_set_gist, [managed] context : UnionSet<'a>);
+    isl_transform!(gist_params, isl_union_set_gist_params, [managed] set : Set<'a>);
+    isl_transform!(apply, isl_union_set_apply, [managed] map : UnionMap<'a>);
+    isl_transform!(coalesce, isl_union_set_coalesce);
+    isl_transform!(detect_equalities, isl_union_set_detect_equalities);
+    isl_transform!(compute_divs, isl_union_set_compute_divs);
+    isl_transform!(affine_hull, isl_union_set_affine_hull);
+    isl_transform!(polyhedral_hull, isl_union_set_polyhedral_hull);
+    isl_transform!(lexmin, isl_union_set_lexmin);
+    isl_transform!(lexmax, isl_union_set_lexmax);
+    isl_transform!([into(UnionMap)] identity, isl_union_set_identity);
+    isl_transform!([into(UnionMap)] lex_lt_union_set, isl_union_set_lex_lt_union_set, [managed] other : UnionSet<'a>);
+    isl_transform!([into(UnionMap)] lex_le_union_set, isl_union_set_lex_le_union_set, [managed] other : UnionSet<'a>);
+    isl_transform!([into(UnionMap)] lex_gt_union_set, isl_union_set_lex_gt_union_set, [managed] other : UnionSet<'a>);
+    isl_transform!([into(UnionMap)] lex_ge_union_set, isl_union_set_lex_ge_union_set, [managed] other : UnionSet<'a>);
+    isl_flag!(union_set_is_params => is_params);
+    isl_flag!(union_set_is_empty => is_empty);
+    isl_flag!(union_set_is_equal => checked_eq, [ref] other : &UnionSet<'a>);
+    isl_flag!(union_set_is_disjoint => disjoint, [ref] other : &UnionSet<'a>);
+    isl_flag!(union_set_is_subset => subset, [ref] other : &UnionSet<'a>);
+    isl_flag!(union_set_is_strict_subset => strict_subset, [ref] other : &UnionSet<'a>);
+}
+
+impl PartialEq for UnionSet<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.checked_eq(other).unwrap_or(false)
+    }
+}
+
+impl<'a> List<'a, UnionSet<'a>> {
+    pub fn union(self) -> UnionSet<'a> {
+        let this = ManuallyDrop::new(self);
+        let handle = unsafe { barvinok_sys::isl_union_set_list_union(this.handle.as_ptr()) };
+        let handle = NonNull::new(handle).unwrap();
+        UnionSet {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<'a> TryFrom<Set<'a>> for UnionSet<'a> {
+    fn try_from(set: Set<'a>) -> Result<Self, crate::Error> {
+        let ctx = set.context_ref();
+        let set = ManuallyDrop::new(set);
+        let handle = unsafe { barvinok_sys::isl_union_set_from_set(set.handle.as_ptr()) };
+        let handle = NonNull::new(handle).ok_or_else(|| ctx.last_error_or_unknown())?;
+        Ok(UnionSet {
+            handle,
+            marker: std::marker::PhantomData,
+        })
+    }
+
+    type Error = crate::Error;
+}
+
+impl<'a> TryFrom<BasicSet<'a>> for UnionSet<'a> {
+    fn try_from(basic_set: BasicSet<'a>) -> Result<Self, crate::Error> {
+        let ctx = basic_set.context_ref();
+        let basic_set = ManuallyDrop::new(basic_set);
+        let handle =
+            unsafe { barvinok_sys::isl_union_set_from_basic_set(basic_set.handle.as_ptr()) };
+        let handle = NonNull::new(handle).ok_or_else(|| ctx.last_error_or_unknown())?;
+        Ok(UnionSet {
+            handle,
+            marker: std::marker::PhantomData,
+        })
+    }
+
+    type Error = crate::Error;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+
+    #[test]
+    fn test_union_set_from_str() {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let uset =
+                UnionSet::from_str(ctx, "[n] -> { S[i] : 0 <= i < n; T[i, j] : 0 <= i, j < n }")
+                    .unwrap();
+            assert_eq!(uset.num_sets().unwrap(), 2);
+            let sets = uset.get_set_list().unwrap();
+            assert_eq!(sets.len(), 2);
+            println!("{:?}", uset);
+        });
+    }
+
+    #[test]
+    fn test_union_set_ops() {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let s = Set::from_str(ctx, "{ S[i] : 0 <= i < 10 }").unwrap();
+            let t = Set::from_str(ctx, "{ T[i] : 0 <= i < 10 }").unwrap();
+            let uset = UnionSet::try_from(s.clone()).unwrap().add_set(t).unwrap();
+            let only_s = uset
+                .clone()
+                .subtract(UnionSet::try_from(s.clone()).unwrap())
+                .unwrap();
+            assert_eq!(only_s.num_sets().unwrap(), 1);
+            let extracted = uset.extract_set(s.get_space().unwrap()).unwrap();
+            assert_eq!(extracted, s);
+            assert!(only_s.subset(&uset).unwrap());
+            assert!(!uset.is_empty().unwrap());
+        });
+    }
+}
@@ -1,13 +1,18 @@
 use num_traits::PrimInt;
 
 use crate::{
-    ContextRef, impl_isl_handle, nonnull_or_alloc_error,
-    stat::{ContextResult, isl_bool_to_optional_bool},
+    ContextRef, impl_isl_handle, isl_ctor, isl_flag, isl_project, isl_size, isl_transform,
+    list::List,
+    nonnull_or_alloc_error,
+    space::Space,
+    stat::{ContextResult, isl_bool_to_optional_bool, isl_size_to_optional_u32},
 };
 
 use std::mem::ManuallyDrop;
+use std::ptr::NonNull;
 
 impl_isl_handle!(Value, val);
+impl_isl_handle!(MultiValue, multi_val);
 
 macro_rules! isl_val_new {
     ($name:ident, $func:ident $(, $arg_name:ident : $arg_ty:ty)*) => {
@@ -101,14 +106,65 @@ impl<'a> Value<'a> {
         }
     }
 
+    /// Create a value from a `u128`, which does not fit in [`Value::new_ui`].
+    pub fn new_u128(ctx: ContextRef<'a>, value: u128) -> Self {
+        Self::new_chunks(ctx, &[value as u64, (value >> 64) as u64])
+    }
+
+    /// Create a value from an `i128`, which does not fit in [`Value::new_si`].
+    pub fn new_i128(ctx: ContextRef<'a>, value: i128) -> Self {
+        let abs = Self::new_u128(ctx, value.unsigned_abs());
+        if value < 0 { -abs } else { abs }
+    }
+
+    /// The numerator, truncated to the low bits of an `i64` if it does not fit. The
+    /// `TryFrom` conversions and [`Value::abs_num_chunks`] are exact.
     pub fn numerator(&self) -> i64 {
         unsafe { barvinok_sys::isl_val_get_num_si(self.handle.as_ptr()) }
     }
 
+    /// The denominator, truncated like [`Value::numerator`].
     pub fn denominator(&self) -> i64 {
         unsafe { barvinok_sys::isl_val_get_den_si(self.handle.as_ptr()) }
     }
 
+    /// The absolute value of the numerator as chunks of `T`, least significant first.
+    /// The inverse of [`Value::new_chunks`] for non-negative integers.
+    pub fn abs_num_chunks<T: PrimInt>(&self) -> crate::Result<Vec<T>> {
+        let size = std::mem::size_of::<T>();
+        let n = unsafe { barvinok_sys::isl_val_n_abs_num_chunks(self.handle.as_ptr(), size) };
+        let n = isl_size_to_optional_u32(n).context_result(self.context_ref())?;
+        let mut chunks = vec![T::zero(); n as usize];
+        let res = unsafe {
+            barvinok_sys::isl_val_get_abs_num_chunks(
+                self.handle.as_ptr(),
+                size,
+                chunks.as_mut_ptr() as *mut std::ffi::c_void,
+            )
+        };
+        if res != barvinok_sys::isl_stat_isl_stat_ok {
+            return Err(self.context_ref().last_error_or_unknown().into());
+        }
+        Ok(chunks)
+    }
+
+    /// Sign and magnitude of an integer value, if the magnitude fits in a `u128`.
+    fn to_sign_magnitude(&self) -> crate::Result<(bool, u128)> {
+        if !self.is_int().context_result(self.context_ref())? {
+            return Err(crate::Error::NonIntegralValue);
+        }
+        let chunks = self.abs_num_chunks::<u64>()?;
+        if chunks.len() > 2 {
+            return Err(crate::Error::ValueOutOfRange);
+        }
+        let magnitude = chunks
+            .iter()
+            .rev()
+            .fold(0u128, |acc, &chunk| (acc << 64) | chunk as u128);
+        let negative = !self.is_nonneg().context_result(self.context_ref())?;
+        Ok((negative, magnitude))
+    }
+
     pub fn new_from_string(ctx: ContextRef<'a>, value: &str) -> crate::Result<Self> {
         let cstr = std::ffi::CString::new(value).map_err(|_| crate::Error::ParseError)?;
         let handle = unsafe { barvinok_sys::isl_val_read_from_str(ctx.0.as_ptr(), cstr.as_ptr()) };
@@ -157,6 +213,16 @@ impl<'a> Value<'a> {
     impl_special_val_check!(is_nonpos, isl_val_is_nonpos);
     impl_special_val_check!(is_int, isl_val_is_int);
     impl_special_val_check!(is_rat, isl_val_is_rat);
+    impl_special_val_check!(is_pos, isl_val_is_pos);
+    impl_special_val_check!(is_neg, isl_val_is_neg);
+
+    /// The sign as `-1`, `0` or `1`, or `None` for NaN.
+    pub fn sgn(&self) -> Option<i32> {
+        if self.is_nan()? {
+            return None;
+        }
+        Some(unsafe { barvinok_sys::isl_val_sgn(self.handle.as_ptr()) })
+    }
 
     pub fn gt_si(&self, value: i64) -> Option<bool> {
         let flag = unsafe { barvinok_sys::isl_val_gt_si(self.handle.as_ptr(), value) };
@@ -190,6 +256,44 @@ impl<'a> Value<'a> {
     impl_binary_method_ui!(mul_ui, isl_val_mul_ui);
     impl_binary_method_ui!(div_ui, isl_val_div_ui);
 
+    /// Raise to the power `exp`, exactly. `pow(0)` is one, even for NaN and infinity.
+    pub fn pow(self, mut exp: u32) -> Self {
+        let mut result = Self::new_one(self.context_ref());
+        let mut base = self;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * base.clone();
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.clone() * base;
+            }
+        }
+        result
+    }
+
+    /// Multiply by `2^exp`.
+    pub fn mul_2exp(self, exp: u32) -> Self {
+        let factor = Self::new_exp2(self.context_ref(), exp);
+        self * factor
+    }
+
+    /// Divide by `2^exp`. The result is exact, not rounded.
+    pub fn div_2exp(self, exp: u32) -> Self {
+        let factor = Self::new_exp2(self.context_ref(), exp);
+        self / factor
+    }
+
+    fn new_exp2(ctx: ContextRef<'a>, exp: u32) -> Self {
+        let exp = ManuallyDrop::new(Self::new_ui(ctx, exp as u64));
+        let handle = unsafe { barvinok_sys::isl_val_pow2(exp.handle.as_ptr()) };
+        let handle = nonnull_or_alloc_error(handle);
+        Self {
+            handle,
+            marker: std::marker::PhantomData,
+        }
+    }
+
     pub fn checked_exp2(self) -> crate::Result<Self> {
         if !self.is_int().context_result(self.context_ref())? {
             return Err(crate::Error::NonIntegralValue);
@@ -277,24 +381,126 @@ impl From<Value<'_>> for f64 {
     }
 }
 
+impl std::fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+/// The sum, or `None` for an empty iterator since there is no context to create zero in.
+impl<'a> std::iter::Sum<Value<'a>> for Option<Value<'a>> {
+    fn sum<I: Iterator<Item = Value<'a>>>(iter: I) -> Self {
+        iter.reduce(|acc, value| acc + value)
+    }
+}
+
+/// The product, or `None` for an empty iterator since there is no context to create one in.
+impl<'a> std::iter::Product<Value<'a>> for Option<Value<'a>> {
+    fn product<I: Iterator<Item = Value<'a>>>(iter: I) -> Self {
+        iter.reduce(|acc, value| acc * value)
+    }
+}
+
+macro_rules! impl_try_from_value {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl TryFrom<Value<'_>> for $ty {
+                type Error = crate::Error;
+
+                fn try_from(value: Value<'_>) -> Result<Self, Self::Error> {
+                    let (negative, magnitude) = value.to_sign_magnitude()?;
+                    let converted = if negative {
+                        0i128
+                            .checked_sub_unsigned(magnitude)
+                            .and_then(|value| <$ty>::try_from(value).ok())
+                    } else {
+                        <$ty>::try_from(magnitude).ok()
+                    };
+                    converted.ok_or(crate::Error::ValueOutOfRange)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_value!(i64, i128, u64, u128);
+
+#[cfg(feature = "num-bigint")]
+impl<'a> Value<'a> {
+    pub fn from_bigint(ctx: ContextRef<'a>, value: &num_bigint::BigInt) -> Self {
+        let abs = Self::new_chunks(ctx, &value.magnitude().to_u32_digits());
+        if value.sign() == num_bigint::Sign::Minus {
+            -abs
+        } else {
+            abs
+        }
+    }
+
+    fn numerator_bigint(&self) -> crate::Result<num_bigint::BigInt> {
+        let sign = if self.is_nonneg().context_result(self.context_ref())? {
+            num_bigint::Sign::Plus
+        } else {
+            num_bigint::Sign::Minus
+        };
+        Ok(num_bigint::BigInt::from_slice(
+            sign,
+            &self.abs_num_chunks::<u32>()?,
+        ))
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl TryFrom<Value<'_>> for num_bigint::BigInt {
+    type Error = crate::Error;
+
+    fn try_from(value: Value<'_>) -> Result<Self, Self::Error> {
+        if !value.is_int().context_result(value.context_ref())? {
+            return Err(crate::Error::NonIntegralValue);
+        }
+        value.numerator_bigint()
+    }
+}
+
+#[cfg(feature = "num-rational")]
+impl<'a> Value<'a> {
+    pub fn from_big_rational(ctx: ContextRef<'a>, value: &num_rational::BigRational) -> Self {
+        Self::from_bigint(ctx, value.numer()) / Self::from_bigint(ctx, value.denom())
+    }
+}
+
+#[cfg(feature = "num-rational")]
+impl TryFrom<Value<'_>> for num_rational::BigRational {
+    type Error = crate::Error;
+
+    fn try_from(value: Value<'_>) -> Result<Self, Self::Error> {
+        if !value.is_rat().context_result(value.context_ref())? {
+            return Err(crate::Error::NonRationalValue);
+        }
+        // ISL keeps rationals reduced with a positive denominator
+        Ok(num_rational::BigRational::new_raw(
+            value.numerator_bigint()?,
+            value.denominator_value().numerator_bigint()?,
+        ))
+    }
+}
+
 macro_rules! impl_cmp_method {
     ($method:ident, $isl_fn:ident) => {
         fn $method(&self, other: &Self) -> bool {
             let flag =
                 unsafe { barvinok_sys::$isl_fn(self.handle.as_ptr(), other.handle.as_ptr()) };
-            isl_bool_to_optional_bool(flag)
-                .context_result(self.context_ref())
-                .unwrap()
+            // ISL only fails on NULL arguments, so this never hides an error
+            isl_bool_to_optional_bool(flag).unwrap_or(false)
         }
     };
 }
 
-#[allow(clippy::partialeq_ne_impl)]
+/// Exact comparison. As for floats, NaN is unequal to everything, itself included.
 impl PartialEq for Value<'_> {
     impl_cmp_method!(eq, isl_val_eq);
-    impl_cmp_method!(ne, isl_val_ne);
 }
 
+/// Infinities are ordered around the rationals. NaN is unordered.
 impl PartialOrd for Value<'_> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         if self.lt(other) {
@@ -351,6 +557,25 @@ impl_bin_op!(Sub, sub, isl_val_sub);
 impl_bin_op!(Mul, mul, isl_val_mul);
 impl_bin_op!(Div, div, isl_val_div);
 
+type ValueList<'a> = List<'a, Value<'a>>;
+
+impl<'a> MultiValue<'a> {
+    isl_ctor!([ctx] from_str, isl_multi_val_read_from_str, [str] str: &str);
+    isl_ctor!(zero, isl_multi_val_zero, space: Space<'a>);
+    isl_ctor!(from_value_list, isl_multi_val_from_val_list, space: Space<'a>, [managed] list: ValueList<'a>);
+    isl_project!([into(Space)] get_space, isl_multi_val_get_space);
+    isl_project!([into(ValueList)] get_list, isl_multi_val_get_list);
+    isl_size!(multi_val_size => size);
+    isl_project!([into(Value)] get_at, isl_multi_val_get_at, [cast(i32)] pos: u32);
+    isl_transform!(set_at, isl_multi_val_set_at, [cast(i32)] pos: u32, [managed] value: Value<'a>);
+    isl_transform!(add_value, isl_multi_val_add_val, [managed] value: Value<'a>);
+    isl_transform!(mod_value, isl_multi_val_mod_val, [managed] value: Value<'a>);
+    isl_transform!(scale_value, isl_multi_val_scale_val, [managed] value: Value<'a>);
+    isl_transform!(range_product, isl_multi_val_range_product, [managed] other: MultiValue<'a>);
+    isl_flag!(multi_val_is_zero => is_zero);
+    isl_flag!(multi_val_plain_is_equal => plain_is_equal, [ref] other: &MultiValue<'a>);
+}
+
 #[cfg(test)]
 mod tests {
     use std::ops::Neg;
@@ -390,6 +615,89 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_value_chunk_export() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let val = Value::new_chunks(ctx, &[0u32, 2, 2]);
+            assert_eq!(val.abs_num_chunks::<u32>()?, vec![0, 2, 2]);
+            assert_eq!(val.clone().neg().abs_num_chunks::<u32>()?, vec![0, 2, 2]);
+            assert_eq!(val.abs_num_chunks::<u64>()?, vec![2u64 << 32, 2]);
+            let val = Value::new_from_string(ctx, "-7/3")?;
+            assert_eq!(val.abs_num_chunks::<u8>()?, vec![7]);
+            assert!(Value::new_nan(ctx).abs_num_chunks::<u8>().is_err());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_value_try_into_integers() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let big = Value::new_from_string(ctx, "-170141183460469231731687303715884105728")?;
+            assert_eq!(i128::try_from(big.clone())?, i128::MIN);
+            assert!(matches!(
+                i64::try_from(big.clone()),
+                Err(crate::Error::ValueOutOfRange)
+            ));
+            assert!(matches!(
+                i128::try_from(big.sub_ui(1)),
+                Err(crate::Error::ValueOutOfRange)
+            ));
+            assert_eq!(u128::try_from(Value::new_u128(ctx, u128::MAX))?, u128::MAX);
+            assert_eq!(i128::try_from(Value::new_i128(ctx, -5))?, -5);
+            assert_eq!(u64::try_from(Value::new_si(ctx, 0))?, 0);
+            assert_eq!(i64::try_from(Value::new_si(ctx, i64::MIN))?, i64::MIN);
+            assert!(matches!(
+                u64::try_from(Value::new_si(ctx, -1)),
+                Err(crate::Error::ValueOutOfRange)
+            ));
+            assert!(matches!(
+                u128::try_from(Value::new_u128(ctx, u128::MAX).add_ui(1)),
+                Err(crate::Error::ValueOutOfRange)
+            ));
+            assert!(matches!(
+                i64::try_from(Value::new_from_string(ctx, "1/2")?),
+                Err(crate::Error::NonIntegralValue)
+            ));
+            assert!(matches!(
+                i64::try_from(Value::new_infty(ctx)),
+                Err(crate::Error::NonIntegralValue)
+            ));
+            Ok(())
+        })
+    }
+
+    #[cfg(feature = "num-rational")]
+    #[test]
+    fn test_value_bignum_conversions() -> anyhow::Result<()> {
+        use num_bigint::BigInt;
+        use num_rational::BigRational;
+
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let text = "-123456789012345678901234567890123456789";
+            let big: BigInt = text.parse()?;
+            let val = Value::new_from_string(ctx, text)?;
+            assert_eq!(BigInt::try_from(val.clone())?, big);
+            assert!(Value::from_bigint(ctx, &big) == val);
+            assert_eq!(BigInt::try_from(Value::new_zero(ctx))?, BigInt::from(0));
+
+            let ratio = BigRational::new(big, BigInt::from(-6));
+            let val = Value::from_big_rational(ctx, &ratio);
+            assert_eq!(BigRational::try_from(val.clone())?, ratio);
+            assert!(matches!(
+                BigInt::try_from(val),
+                Err(crate::Error::NonIntegralValue)
+            ));
+            assert!(matches!(
+                BigRational::try_from(Value::new_nan(ctx)),
+                Err(crate::Error::NonRationalValue)
+            ));
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_value_abs_eq() {
         let ctx = Context::new();
@@ -416,6 +724,82 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_value_nan_and_infinity() {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let one = Value::new_one(ctx);
+            let nan = Value::new_nan(ctx);
+            let infty = Value::new_infty(ctx);
+            let neg_infty = Value::new_neg_infty(ctx);
+
+            assert!(nan != nan);
+            assert!(nan != one);
+            assert_eq!(nan.partial_cmp(&one), None);
+            assert!(!nan.lt(&one) && !nan.ge(&one));
+            assert!(infty == Value::new_infty(ctx));
+            assert!(neg_infty < one && one < infty);
+            assert_eq!(
+                neg_infty.partial_cmp(&infty),
+                Some(std::cmp::Ordering::Less)
+            );
+
+            assert!((infty.clone() + neg_infty.clone()).is_nan().unwrap());
+            assert!((one.clone() / Value::new_zero(ctx)).is_nan().unwrap());
+            assert!((nan.clone() + one.clone()).is_nan().unwrap());
+            assert!((infty.clone() + one.clone()) == infty);
+            assert!(infty.clone().neg() == neg_infty);
+
+            assert_eq!(nan.sgn(), None);
+            assert_eq!(neg_infty.sgn(), Some(-1));
+            assert!(infty.is_pos().unwrap() && neg_infty.is_neg().unwrap());
+            assert!(!nan.is_pos().unwrap() && !nan.is_neg().unwrap());
+        });
+    }
+
+    #[test]
+    fn test_value_pow_and_shifts() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let val = Value::new_from_string(ctx, "-2/3")?;
+            assert!(val.clone().pow(0) == Value::new_one(ctx));
+            assert!(val.clone().pow(1) == val);
+            assert_eq!(val.clone().pow(5).to_string(), "-32/243");
+            assert_eq!(
+                Value::new_si(ctx, 10).pow(40).to_string(),
+                format!("1{}", "0".repeat(40))
+            );
+
+            assert_eq!(val.clone().mul_2exp(3).to_string(), "-16/3");
+            assert_eq!(val.clone().div_2exp(3).to_string(), "-1/12");
+            assert!(Value::new_si(ctx, 5).mul_2exp(100).div_2exp(100) == Value::new_si(ctx, 5));
+
+            assert_eq!(val.sgn(), Some(-1));
+            assert_eq!(Value::new_zero(ctx).sgn(), Some(0));
+            assert!(val.is_neg().unwrap() && !val.is_pos().unwrap());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_value_display_sum_product() -> anyhow::Result<()> {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            assert_eq!(Value::new_from_string(ctx, "6/4")?.to_string(), "3/2");
+            assert_eq!(Value::new_infty(ctx).to_string(), "infty");
+            assert_eq!(format!("{}", Value::new_nan(ctx)), "NaN");
+
+            let values = || (1..=5).map(|n| Value::new_si(ctx, n));
+            assert_eq!(values().sum::<Option<Value>>().unwrap().to_string(), "15");
+            assert_eq!(
+                values().product::<Option<Value>>().unwrap().to_string(),
+                "120"
+            );
+            assert!(std::iter::empty::<Value>().sum::<Option<Value>>().is_none());
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_value_special() {
         let ctx = Context::new();
@@ -598,6 +982,25 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_multi_value() {
+        let ctx = Context::new();
+        ctx.scope(|ctx| {
+            let space = Space::set(ctx, 0, 2).unwrap();
+            let mut list = ValueList::new(ctx, 2);
+            list.push(Value::new_si(ctx, 32));
+            list.push(Value::new_si(ctx, 16));
+            let mv = MultiValue::from_value_list(space, list).unwrap();
+            assert_eq!(mv.size().unwrap(), 2);
+            assert!(mv.get_at(1).unwrap() == Value::new_si(ctx, 16));
+            let parsed = MultiValue::from_str(ctx, "{ [32, 16] }").unwrap();
+            assert!(mv.plain_is_equal(&parsed).unwrap());
+            let mv = mv.set_at(0, Value::new_si(ctx, 0)).unwrap();
+            let mv = mv.set_at(1, Value::new_si(ctx, 0)).unwrap();
+            assert!(mv.is_zero().unwrap());
+        });
+    }
+
     #[test]
     fn test_add_to_list() {
         type ValueList<'a> = crate::list::List<'a, Value<'a>>;